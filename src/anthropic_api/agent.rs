@@ -0,0 +1,235 @@
+use super::{
+    client::AnthropicClient,
+    content::{ContentBlock, ToolUse},
+    conversation::Conversation,
+    error::Result,
+    messages::StopReason,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::HashMap, collections::VecDeque, future::Future, pin::Pin};
+
+/// Default number of requests [`AgentLoop`] sends before giving up.
+pub const DEFAULT_MAX_STEPS: usize = 16;
+
+/// Future returned by a tool executor.
+pub type ToolFuture = Pin<Box<dyn Future<Output = ContentBlock> + Send>>;
+
+/// Tool executor stored by the agent loop.
+type ToolExecutor = Box<dyn Fn(ToolUse) -> ToolFuture + Send + Sync>;
+
+/// Enum representing an event of an agent turn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AgentEvent {
+    /// Text written by the model.
+    AssistantText(String),
+
+    /// A tool call made by the model, about to be executed.
+    ToolCall(ToolUse),
+
+    /// The result of a tool call, as sent back to the model.
+    ToolResult(ContentBlock),
+
+    /// The model gave its final answer; carries the last JSON response.
+    TurnComplete(Value),
+}
+
+/// [`AgentLoop`] struct driving a multi-step agent turn.
+///
+/// The model is called, the tools it asks for are executed and their results
+/// sent back, and this repeats until the model answers without calling a tool.
+/// Every step is reported as an [`AgentEvent`] by [`AgentLoop::next_event`];
+/// between two events the conversation can be inspected or amended through
+/// [`AgentLoop::conversation_mut`].
+pub struct AgentLoop<'a> {
+    /// The client used to send the requests.
+    client: &'a AnthropicClient,
+
+    /// The conversation the turn is part of.
+    conversation: Conversation,
+
+    /// Executors of the tools, by tool name.
+    tools: HashMap<String, ToolExecutor>,
+
+    /// Maximum number of requests sent for the turn.
+    max_steps: usize,
+
+    /// Number of requests sent so far.
+    steps: usize,
+
+    /// Events ready to be returned.
+    events: VecDeque<AgentEvent>,
+
+    /// The last response of the model, once a request was sent.
+    last_response: Option<Value>,
+
+    /// Whether the turn is over.
+    finished: bool,
+}
+
+impl<'a> AgentLoop<'a> {
+    /// Create a new instance of [`AgentLoop`].
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`AnthropicClient`] used to send the requests.
+    /// * `conversation` - The conversation to continue, ending with the user
+    ///   message starting the turn and holding the tool definitions.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`AgentLoop`] without tool executors.
+    pub fn new(client: &'a AnthropicClient, conversation: Conversation) -> Self {
+        Self {
+            client,
+            conversation,
+            tools: HashMap::new(),
+            max_steps: DEFAULT_MAX_STEPS,
+            steps: 0,
+            events: VecDeque::new(),
+            last_response: None,
+            finished: false,
+        }
+    }
+
+    /// Register the executor of a tool.
+    ///
+    /// The executor must answer with a tool result referencing the id of the
+    /// call, e.g. built with [`ContentBlock::tool_result`] or
+    /// [`ContentBlock::tool_result_error`]. Calls to a tool without executor
+    /// are answered with an error result.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tool, as in its definition.
+    /// * `executor` - An async function executing a call of the tool.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`AgentLoop`].
+    pub fn tool<F, Fut>(mut self, name: impl Into<String>, executor: F) -> Self
+    where
+        F: Fn(ToolUse) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ContentBlock> + Send + 'static,
+    {
+        self.tools.insert(
+            name.into(),
+            Box::new(move |tool_use| Box::pin(executor(tool_use))),
+        );
+        self
+    }
+
+    /// Set the maximum number of requests sent for the turn.
+    ///
+    /// Once reached, the turn completes after executing the pending tool
+    /// calls, without sending their results to the model.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_steps` - The maximum number of requests.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`AgentLoop`].
+    #[inline(always)]
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Get the conversation, including the steps of the turn so far.
+    #[inline(always)]
+    pub fn conversation(&self) -> &Conversation {
+        &self.conversation
+    }
+
+    /// Get the conversation mutably, e.g. to add a message between two steps.
+    #[inline(always)]
+    pub fn conversation_mut(&mut self) -> &mut Conversation {
+        &mut self.conversation
+    }
+
+    /// Finish the loop and return the conversation.
+    #[inline(always)]
+    pub fn into_conversation(self) -> Conversation {
+        self.conversation
+    }
+
+    /// Advance the turn to its next event.
+    ///
+    /// # Returns
+    ///
+    /// The next [`AgentEvent`], an [`AnthropicError`] if a request failed or a
+    /// tool result did not match its call, or `None` once the turn completed.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub async fn next_event(&mut self) -> Option<Result<AgentEvent>> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.finished {
+                return None;
+            }
+
+            if let Some(tool_use) = self.conversation.pending_tool_uses().into_iter().next() {
+                return Some(self.execute(tool_use).await);
+            }
+
+            if let Some(response) = &self.last_response {
+                let stop_reason = StopReason::deserialize(&response["stop_reason"]).ok();
+                if stop_reason != Some(StopReason::ToolUse) || self.steps >= self.max_steps {
+                    self.finished = true;
+                    return Some(Ok(AgentEvent::TurnComplete(response.clone())));
+                }
+            }
+
+            self.steps += 1;
+            let response = match self.conversation.send_raw(self.client).await {
+                Ok(response) => response,
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                },
+            };
+            self.queue_response(&response);
+            self.last_response = Some(response);
+        }
+    }
+
+    /// Execute a pending tool call and record its result.
+    async fn execute(&mut self, tool_use: ToolUse) -> Result<AgentEvent> {
+        let result = match self.tools.get(&tool_use.name) {
+            Some(executor) => executor(tool_use).await,
+            None => ContentBlock::tool_result_error(
+                &tool_use.id,
+                format!("no tool named {:?} is available", tool_use.name),
+            ),
+        };
+
+        if let Err(error) = self.conversation.add_tool_result(result.clone()) {
+            self.finished = true;
+            return Err(error);
+        }
+        Ok(AgentEvent::ToolResult(result))
+    }
+
+    /// Queue the text and tool call events of a response.
+    fn queue_response(&mut self, response: &Value) {
+        let blocks = response["content"].as_array().into_iter().flatten();
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("text") => {
+                    let text = block["text"].as_str().unwrap_or_default().to_owned();
+                    self.events.push_back(AgentEvent::AssistantText(text));
+                },
+                Some("tool_use") => {
+                    if let Ok(tool_use) = ToolUse::deserialize(block) {
+                        self.events.push_back(AgentEvent::ToolCall(tool_use));
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{error::Error, future::Future, pin::Pin, sync::Arc};
+
+/// Placeholder replacing secret header values in an [`AuditRecord`].
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Error type returned by an audit hook.
+pub type AuditError = Box<dyn Error + Send + Sync>;
+
+/// Future returned by an audit hook.
+pub type AuditFuture = Pin<Box<dyn Future<Output = Result<(), AuditError>> + Send>>;
+
+/// Shared audit hook stored by the client.
+pub(crate) type AuditHook = Arc<dyn Fn(AuditRecord) -> AuditFuture + Send + Sync>;
+
+/// Struct representing a request about to be sent, as seen by an audit hook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// HTTP method of the request.
+    pub method: String,
+
+    /// Full URL of the request.
+    pub url: String,
+
+    /// Headers of the request, with the API key replaced by [`REDACTED`].
+    pub headers: Vec<(String, String)>,
+
+    /// Serialized request body, byte-for-byte what is sent.
+    pub body: String,
+}
+
+/// Wrap an async closure into an [`AuditHook`].
+pub(crate) fn hook<F, Fut>(hook: F) -> AuditHook
+where
+    F: Fn(AuditRecord) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), AuditError>> + Send + 'static,
+{
+    Arc::new(move |record| Box::pin(hook(record)))
+}
@@ -0,0 +1,502 @@
+use super::{
+    client::{AnthropicClient, RequestOptions, StreamGuard},
+    error::{AnthropicError, Result},
+    messages::{MessageRequest, MessageResponse},
+    page::{ListParams, Page},
+    retry::RetryPolicy,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    io::BufRead,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{self, Instant};
+
+/// Body of a batch results file, as a stream of byte chunks.
+type ByteStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// [`BatchesApi`] struct to interact with the message batches endpoints of the API.
+pub struct BatchesApi<'a>(pub(crate) &'a AnthropicClient);
+
+/// Struct representing a request of a message batch.
+#[derive(Serialize)]
+pub struct BatchRequest {
+    /// Id of the request, unique within the batch, used to match its result.
+    pub custom_id: String,
+
+    /// Parameters of the request, as sent to the messages endpoint.
+    pub params: MessageRequest,
+}
+
+impl From<(String, MessageRequest)> for BatchRequest {
+    #[inline(always)]
+    fn from((custom_id, params): (String, MessageRequest)) -> Self {
+        Self { custom_id, params }
+    }
+}
+
+/// Struct representing the body of a batch creation request.
+#[derive(Serialize)]
+struct CreateBatch<'a> {
+    /// The requests of the batch.
+    requests: &'a [BatchRequest],
+}
+
+/// Enum representing the processing status of a message batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessingStatus {
+    /// Requests of the batch are being processed.
+    InProgress,
+
+    /// The batch is being canceled.
+    Canceling,
+
+    /// Every request of the batch ended, and its results are available.
+    Ended,
+
+    /// Any status not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Struct representing the number of requests of a batch per outcome.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RequestCounts {
+    /// Number of requests still being processed.
+    pub processing: u64,
+
+    /// Number of requests that succeeded.
+    pub succeeded: u64,
+
+    /// Number of requests that failed.
+    pub errored: u64,
+
+    /// Number of requests canceled before being sent.
+    pub canceled: u64,
+
+    /// Number of requests that expired before being sent.
+    pub expired: u64,
+}
+
+/// Struct representing a message batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MessageBatch {
+    /// Unique object identifier.
+    pub id: String,
+
+    /// Processing status of the batch.
+    pub processing_status: ProcessingStatus,
+
+    /// Number of requests of the batch per outcome.
+    pub request_counts: RequestCounts,
+
+    /// When the batch was created, as an RFC 3339 datetime.
+    pub created_at: String,
+
+    /// When the batch expires if still in progress, as an RFC 3339 datetime.
+    pub expires_at: String,
+
+    /// When every request of the batch ended, if so.
+    #[serde(default)]
+    pub ended_at: Option<String>,
+
+    /// When the cancellation of the batch was requested, if so.
+    #[serde(default)]
+    pub cancel_initiated_at: Option<String>,
+
+    /// When the batch was archived and its results removed, if so.
+    #[serde(default)]
+    pub archived_at: Option<String>,
+
+    /// URL of the results file, once the batch ended.
+    #[serde(default)]
+    pub results_url: Option<String>,
+}
+
+/// Struct representing the confirmation of a message batch deletion.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DeletedBatch {
+    /// Id of the deleted batch.
+    pub id: String,
+}
+
+impl BatchesApi<'_> {
+    /// Create a message batch.
+    ///
+    /// Each request is validated and completed with the defaults of the client
+    /// as [`MessagesApi::create`] would. Results are matched to requests by
+    /// their `custom_id`, so the ids must be unique within the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `requests` - The requests of the batch, either [`BatchRequest`]s or
+    ///   `(custom_id, request)` pairs.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the created [`MessageBatch`] on success, or an
+    /// [`AnthropicError`] on failure.
+    ///
+    /// [`MessagesApi::create`]: super::messages::MessagesApi::create
+    pub async fn create<R: Into<BatchRequest>>(
+        &self,
+        requests: impl IntoIterator<Item = R>,
+    ) -> Result<MessageBatch> {
+        let mut custom_ids = HashSet::new();
+        let mut checked = Vec::new();
+        for request in requests {
+            let BatchRequest { custom_id, params } = request.into();
+            if !custom_ids.insert(custom_id.clone()) {
+                return Err(AnthropicError::Validation(format!(
+                    "custom_id {custom_id:?} is used by several requests of the batch"
+                )));
+            }
+
+            let params = self.0.messages().prepare(params, false)?;
+            checked.push(BatchRequest { custom_id, params });
+        }
+
+        self.0
+            .post("/messages/batches", &CreateBatch { requests: &checked })
+            .await
+    }
+
+    /// Retrieve the status of a message batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The id of the batch.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageBatch`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn retrieve(&self, batch_id: &str) -> Result<MessageBatch> {
+        self.0.get(&format!("/messages/batches/{batch_id}")).await
+    }
+
+    /// List the message batches of the workspace, most recent first.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The [`ListParams`] selecting the page.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`Page`] of [`MessageBatch`]es on success,
+    /// or an [`AnthropicError`] on failure.
+    pub async fn list(&self, params: &ListParams) -> Result<Page<MessageBatch>> {
+        self.0.get(&params.to_path("/messages/batches")).await
+    }
+
+    /// Cancel a message batch.
+    ///
+    /// The batch moves to [`ProcessingStatus::Canceling`] until requests
+    /// already being processed end; requests not yet sent are canceled.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The id of the batch.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageBatch`] as of the cancellation on
+    /// success, or an [`AnthropicError`] on failure.
+    pub async fn cancel(&self, batch_id: &str) -> Result<MessageBatch> {
+        let url = format!("/messages/batches/{batch_id}/cancel");
+        let (batch, _) = self
+            .0
+            .send(Method::POST, &url, None, &RequestOptions::default())
+            .await?;
+        Ok(batch)
+    }
+
+    /// Delete a message batch and its results.
+    ///
+    /// Only batches that ended can be deleted; cancel a batch in progress
+    /// first.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The id of the batch.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`DeletedBatch`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn delete(&self, batch_id: &str) -> Result<DeletedBatch> {
+        self.0
+            .delete(&format!("/messages/batches/{batch_id}"))
+            .await
+    }
+
+    /// Stream the results of an ended message batch.
+    ///
+    /// The results are parsed line by line as they are downloaded, see
+    /// [`BatchResults`]. Their order does not match the order of the requests;
+    /// match them by [`BatchResultLine::custom_id`].
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The id of the batch.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`BatchResults`] on success, or an
+    /// [`AnthropicError`] on failure, e.g. if the batch did not end yet.
+    pub async fn results(&self, batch_id: &str) -> Result<BatchResults> {
+        let (response, guard) = self
+            .0
+            .get_stream(
+                &format!("/messages/batches/{batch_id}/results"),
+                &RequestOptions::default(),
+            )
+            .await?;
+        Ok(BatchResults::new(response.bytes_stream()).guarded(guard))
+    }
+
+    /// Poll a message batch until it ended.
+    ///
+    /// A batch ends once every request succeeded, failed, or was canceled or
+    /// expired; check [`MessageBatch::request_counts`] for the outcome. Rate
+    /// limited, overloaded, and network errors do not stop the polling: the
+    /// interval backs off exponentially, up to eight times `poll_interval`,
+    /// until a poll succeeds again. Other errors are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_id` - The id of the batch.
+    /// * `poll_interval` - The delay between two polls.
+    /// * `timeout` - How long to wait for the batch to end.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the ended [`MessageBatch`] on success,
+    /// [`AnthropicError::Timeout`] if it did not end in time, or another
+    /// [`AnthropicError`] on failure.
+    pub async fn wait_for_completion(
+        &self,
+        batch_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<MessageBatch> {
+        let deadline = Instant::now() + timeout;
+        let backoff = RetryPolicy::new()
+            .base_delay(poll_interval)
+            .max_delay(poll_interval.saturating_mul(8));
+        let mut failures = 0;
+
+        loop {
+            let delay = match self.retrieve(batch_id).await {
+                Ok(batch) if batch.processing_status == ProcessingStatus::Ended => {
+                    return Ok(batch)
+                },
+                Ok(_) => {
+                    failures = 0;
+                    poll_interval
+                },
+                Err(error) if is_transient(&error) => {
+                    failures += 1;
+                    backoff.backoff(failures - 1)
+                },
+                Err(error) => return Err(error),
+            };
+
+            if Instant::now() + delay > deadline {
+                return Err(AnthropicError::Timeout(timeout));
+            }
+            time::sleep(delay).await;
+        }
+    }
+}
+
+/// Whether an error is worth polling again after, i.e. is not about the request.
+fn is_transient(error: &AnthropicError) -> bool {
+    match error {
+        AnthropicError::Http(_) => true,
+        AnthropicError::Api { status, .. } => {
+            *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+        },
+        _ => false,
+    }
+}
+
+/// Struct representing a line of the results of a message batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BatchResultLine {
+    /// Id given to the request when the batch was created.
+    pub custom_id: String,
+
+    /// Outcome of the request.
+    pub result: BatchResult,
+}
+
+/// Enum representing the outcome of a request of a message batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchResult {
+    /// The request succeeded.
+    Succeeded {
+        /// The response to the request.
+        message: Box<MessageResponse>,
+    },
+
+    /// The request failed.
+    Errored {
+        /// The error object, as returned by the messages endpoint.
+        error: Value,
+    },
+
+    /// The batch was canceled before the request was sent.
+    Canceled,
+
+    /// The batch expired before the request was sent.
+    Expired,
+
+    /// Any result type not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+impl BatchResultLine {
+    /// Parse the lines of a batch results file read from disk.
+    ///
+    /// Lines are read and parsed one at a time, so the file is never loaded
+    /// in full. Blank lines are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader of the JSONL results.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the parsed lines, failing on lines that cannot be read
+    /// or parsed without stopping the iteration.
+    pub fn parse_lines(reader: impl BufRead) -> impl Iterator<Item = Result<Self>> {
+        reader
+            .lines()
+            .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+    }
+}
+
+/// [`BatchResults`] struct yielding the lines of batch results as they arrive.
+///
+/// The body is split into lines as chunks are received and each line is
+/// parsed on its own, so results files of any size are processed with the
+/// memory of a single line. A line that fails to parse yields an error and the
+/// following lines are still parsed.
+pub struct BatchResults {
+    /// Body of the results, `None` once it ended.
+    body: Option<ByteStream>,
+
+    /// Bytes received, of which those before `start` were already parsed.
+    buffer: Vec<u8>,
+
+    /// Position of the first byte of `buffer` not yet split into lines.
+    start: usize,
+
+    /// Registration of the download as in flight on the client until the body
+    /// ends, if downloaded by [`BatchesApi::results`].
+    guard: Option<StreamGuard>,
+}
+
+impl BatchResults {
+    /// Create a new instance of [`BatchResults`].
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The body of the results, e.g. from
+    ///   [`reqwest::Response::bytes_stream`].
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`BatchResults`].
+    pub fn new(body: impl Stream<Item = reqwest::Result<Bytes>> + Send + 'static) -> Self {
+        Self {
+            body: Some(Box::pin(body)),
+            buffer: Vec::new(),
+            start: 0,
+            guard: None,
+        }
+    }
+
+    /// Keep the download in flight on its client until the body ends.
+    fn guarded(mut self, guard: StreamGuard) -> Self {
+        self.guard = Some(guard);
+        self
+    }
+
+    /// Parse the next complete line, if any.
+    ///
+    /// Once the body ended, the last line is complete even without a newline.
+    fn next_line(&mut self) -> Option<Result<BatchResultLine>> {
+        loop {
+            let pending = &self.buffer[self.start..];
+            let (line, consumed) = match pending.iter().position(|&byte| byte == b'\n') {
+                Some(end) => (&pending[..end], end + 1),
+                None if self.body.is_none() && !pending.is_empty() => (pending, pending.len()),
+                None => return None,
+            };
+            self.start += consumed;
+
+            if !line.trim_ascii().is_empty() {
+                return Some(serde_json::from_slice(line).map_err(Into::into));
+            }
+        }
+    }
+}
+
+impl Stream for BatchResults {
+    type Item = Result<BatchResultLine>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this
+            .guard
+            .as_mut()
+            .is_some_and(|guard| guard.poll_cancelled(cx).is_ready())
+        {
+            this.body = None;
+            this.guard = None;
+            this.buffer.clear();
+            this.start = 0;
+            return Poll::Ready(Some(Err(AnthropicError::Cancelled)));
+        }
+
+        loop {
+            if let Some(line) = this.next_line() {
+                return Poll::Ready(Some(line));
+            }
+
+            let Some(body) = &mut this.body else {
+                this.guard = None;
+                return Poll::Ready(None);
+            };
+            match body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.buffer.drain(..this.start);
+                    this.start = 0;
+                    this.buffer.extend_from_slice(&chunk);
+                },
+                Poll::Ready(Some(Err(error))) => {
+                    this.body = None;
+                    this.guard = None;
+                    this.buffer.clear();
+                    this.start = 0;
+                    return Poll::Ready(Some(Err(error.into())));
+                },
+                Poll::Ready(None) => this.body = None,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::messages::MessageRequest;
+use crate::setters;
+use serde_json::{json, Value};
+
+/// [`CachedPrompt`] struct assembling requests around a cached stable prefix.
+///
+/// Prompt caching reuses the longest prefix of a request that ends on a
+/// `cache_control` breakpoint and was sent before. The API reads a request as
+/// tools, then system prompt, then messages, so the parts that stay the same
+/// across requests must come first and the breakpoint must end them: content
+/// after a breakpoint is never cached, and a breakpoint placed past changing
+/// content never hits.
+///
+/// Each stable part set here, tools, system prompt, and context messages such
+/// as a long document, ends with a breakpoint, using three of the four the API
+/// allows. A request changing only its context therefore still reuses the
+/// cached tools and system prompt. Prefixes shorter than the minimum cacheable
+/// length of the model, e.g. 1024 tokens, are not cached.
+#[derive(Clone, Debug, Default)]
+pub struct CachedPrompt {
+    /// Model name to be used for generating messages.
+    model: String,
+
+    /// Optional stable system prompt.
+    system: Option<String>,
+
+    /// Optional stable definitions of the tools the model may call.
+    tools: Option<Vec<Value>>,
+
+    /// Optional stable messages sent before those of each request.
+    context: Option<Vec<Value>>,
+}
+
+impl CachedPrompt {
+    /// Create a new instance of [`CachedPrompt`].
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model name to use for generating messages.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`CachedPrompt`] with an empty stable prefix.
+    #[inline(always)]
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    setters! {
+        /// Set the stable system prompt.
+        ///
+        /// # Arguments
+        ///
+        /// * `system` - The system prompt, identical across requests.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`CachedPrompt`].
+        system: impl Into<String>,
+
+        /// Set the stable tools the model may call.
+        ///
+        /// # Arguments
+        ///
+        /// * `tools` - The tool definitions, identical and in the same order
+        ///   across requests.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`CachedPrompt`].
+        tools: Vec<Value>,
+
+        /// Set the stable messages sent before those of each request.
+        ///
+        /// # Arguments
+        ///
+        /// * `context` - The messages, e.g. a user message holding a long
+        ///   document, identical across requests.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`CachedPrompt`].
+        context: Vec<Value>,
+    }
+
+    /// Build a request from the stable prefix and the messages of a turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The messages following the stable context, e.g. the
+    ///   question of the user.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`MessageRequest`] with `cache_control` breakpoints
+    /// at the end of each stable part, ready for further settings such as
+    /// `max_tokens`.
+    pub fn request(&self, messages: impl IntoIterator<Item = Value>) -> MessageRequest {
+        let mut context = self.context.clone().unwrap_or_default();
+        if let Some(block) = context.last_mut().and_then(last_block) {
+            mark_breakpoint(block);
+        }
+
+        let messages = context.into_iter().chain(messages).collect();
+        let mut request = MessageRequest::new(self.model.clone(), messages);
+        if let Some(system) = &self.system {
+            let mut block = json!({ "type": "text", "text": system });
+            mark_breakpoint(&mut block);
+            request = request.system_blocks(vec![block]);
+        }
+        if let Some(tools) = &self.tools {
+            let mut tools = tools.clone();
+            if let Some(tool) = tools.last_mut() {
+                mark_breakpoint(tool);
+            }
+            request = request.tools(tools);
+        }
+
+        request
+    }
+}
+
+/// Get the last content block of a message, turning string content into a block.
+fn last_block(message: &mut Value) -> Option<&mut Value> {
+    let content = message.get_mut("content")?;
+    if let Value::String(text) = content {
+        *content = json!([{ "type": "text", "text": text }]);
+    }
+
+    content.as_array_mut()?.last_mut()
+}
+
+/// Mark a content block, system block, or tool as the end of a cached prefix.
+///
+/// The block gets an ephemeral `cache_control`, so everything up to and
+/// including it is cached; see [`CachedPrompt`] for where breakpoints belong.
+///
+/// # Arguments
+///
+/// * `block` - The block or tool to mark, e.g. a
+///   [`ContentBlock`](super::content::ContentBlock) or a
+///   [`Tool`](super::tools::Tool).
+///
+/// # Returns
+///
+/// The block as a [`Value`], with its `cache_control` set.
+pub fn cache_control(block: impl Into<Value>) -> Value {
+    let mut block = block.into();
+    mark_breakpoint(&mut block);
+    block
+}
+
+/// Mark a block or tool as the end of a cached prefix.
+pub(crate) fn mark_breakpoint(block: &mut Value) {
+    if let Some(block) = block.as_object_mut() {
+        block.insert("cache_control".into(), json!({ "type": "ephemeral" }));
+    }
+}
@@ -0,0 +1,217 @@
+use super::error::{AnthropicError, Result};
+use reqwest::StatusCode;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Default number of consecutive server errors opening the circuit.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Default window the consecutive server errors must happen within.
+pub const DEFAULT_FAILURE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Default time the circuit stays open before a trial request.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Enum representing the state of a [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+
+    /// Requests fail fast until the cooldown has passed.
+    Open,
+
+    /// The cooldown has passed; the next request is a trial deciding whether
+    /// the circuit closes or opens again.
+    HalfOpen,
+}
+
+/// Struct holding the mutable state of a [`CircuitBreaker`].
+#[derive(Default)]
+struct BreakerState {
+    /// Number of consecutive server errors.
+    failures: u32,
+
+    /// When the first of the consecutive server errors happened.
+    first_failure: Option<Instant>,
+
+    /// When the circuit opened, while it is open.
+    opened_at: Option<Instant>,
+
+    /// When the trial request was let through, while it is in flight.
+    trial_started: Option<Instant>,
+}
+
+/// [`CircuitBreaker`] struct failing requests fast while the API is overloaded.
+///
+/// Retrying against an overloaded API makes things worse for everyone, so
+/// once `failure_threshold` consecutive server errors (`529 overloaded` or any
+/// other `5xx`) happen within `failure_window`, the circuit opens and requests
+/// fail with [`AnthropicError::CircuitOpen`] without reaching the network.
+/// After `cooldown`, a single trial request is let through: the circuit closes
+/// if it succeeds and opens again if it fails. Any response other than a
+/// server error counts as a success.
+///
+/// Clones share the same state, so a clone can be kept to monitor the breaker
+/// of a client with [`CircuitBreaker::state`].
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    /// Number of consecutive server errors opening the circuit.
+    failure_threshold: u32,
+
+    /// Window the consecutive server errors must happen within.
+    failure_window: Duration,
+
+    /// Time the circuit stays open before a trial request.
+    cooldown: Duration,
+
+    /// The state shared by the clones of the breaker.
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            failure_window: DEFAULT_FAILURE_WINDOW,
+            cooldown: DEFAULT_COOLDOWN,
+            state: Arc::default(),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a new instance of [`CircuitBreaker`].
+    ///
+    /// # Returns
+    ///
+    /// A new, closed instance of [`CircuitBreaker`] with the default limits.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of consecutive server errors opening the circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `failure_threshold` - How many server errors in a row open the circuit.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`CircuitBreaker`].
+    #[inline(always)]
+    pub fn failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold;
+        self
+    }
+
+    /// Set the window the consecutive server errors must happen within.
+    ///
+    /// # Arguments
+    ///
+    /// * `failure_window` - The longest time from the first to the last of the
+    ///   server errors opening the circuit.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`CircuitBreaker`].
+    #[inline(always)]
+    pub fn failure_window(mut self, failure_window: Duration) -> Self {
+        self.failure_window = failure_window;
+        self
+    }
+
+    /// Set the time the circuit stays open before a trial request.
+    ///
+    /// # Arguments
+    ///
+    /// * `cooldown` - How long requests fail fast once the circuit opened.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`CircuitBreaker`].
+    #[inline(always)]
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Get the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let state = self.lock();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Let a request through, or fail if the circuit is open.
+    pub(crate) fn acquire(&self) -> Result<()> {
+        let mut state = self.lock();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.cooldown {
+            return Err(AnthropicError::CircuitOpen {
+                retry_in: self.cooldown - elapsed,
+            });
+        }
+
+        // A trial that never reported back, e.g. because it was dropped, is
+        // given up on after another cooldown.
+        let now = Instant::now();
+        if let Some(trial_started) = state.trial_started {
+            let elapsed = now.duration_since(trial_started);
+            if elapsed < self.cooldown {
+                return Err(AnthropicError::CircuitOpen {
+                    retry_in: self.cooldown - elapsed,
+                });
+            }
+        }
+
+        state.trial_started = Some(now);
+        Ok(())
+    }
+
+    /// Record the status of the response to a request that was let through.
+    pub(crate) fn record(&self, status: StatusCode) {
+        let mut state = self.lock();
+        if !status.is_server_error() {
+            *state = BreakerState::default();
+            return;
+        }
+
+        let now = Instant::now();
+        if state.opened_at.is_some() {
+            state.opened_at = Some(now);
+            state.trial_started = None;
+            return;
+        }
+
+        match state.first_failure {
+            Some(first_failure) if now.duration_since(first_failure) <= self.failure_window => {
+                state.failures += 1;
+            },
+            _ => {
+                state.failures = 1;
+                state.first_failure = Some(now);
+            },
+        }
+        if state.failures >= self.failure_threshold {
+            state.opened_at = Some(now);
+        }
+    }
+
+    /// Lock the shared state, recovering it if a holder panicked.
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
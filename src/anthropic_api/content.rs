@@ -0,0 +1,872 @@
+use super::{
+    cache,
+    error::{AnthropicError, Result},
+    tokens::truncate_to_tokens,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{borrow::Cow, fmt};
+
+/// Struct representing an image format accepted by the API.
+struct ImageFormat {
+    /// Media type of the format.
+    media_type: &'static str,
+
+    /// Human readable name of the format.
+    name: &'static str,
+
+    /// Whether decoded bytes start with the magic bytes of the format.
+    matches: fn(&[u8]) -> bool,
+}
+
+/// Table of image formats accepted by the API.
+const IMAGE_FORMATS: [ImageFormat; 4] = [
+    ImageFormat {
+        media_type: "image/jpeg",
+        name: "JPEG",
+        matches: |bytes| bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+    },
+    ImageFormat {
+        media_type: "image/png",
+        name: "PNG",
+        matches: |bytes| bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]),
+    },
+    ImageFormat {
+        media_type: "image/gif",
+        name: "GIF",
+        matches: |bytes| bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+    },
+    ImageFormat {
+        media_type: "image/webp",
+        name: "WebP",
+        matches: |bytes| bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP"),
+    },
+];
+
+/// Common misspellings of accepted media types, with their canonical form.
+const MEDIA_TYPE_ALIASES: [(&str, &str); 4] = [
+    ("image/jpg", "image/jpeg"),
+    ("image/pjpeg", "image/jpeg"),
+    ("image/x-png", "image/png"),
+    ("image/x-webp", "image/webp"),
+];
+
+/// Enum representing the role of a message author.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// A message written by the user.
+    User,
+
+    /// A message written by the model.
+    Assistant,
+}
+
+/// Enum representing the source of an image content block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    /// Image data embedded as a base64 string.
+    Base64 {
+        /// Media type of the image, e.g. `image/png`.
+        media_type: String,
+
+        /// Base64 encoded image data.
+        data: String,
+    },
+
+    /// Image referenced by URL.
+    Url {
+        /// URL of the image.
+        url: String,
+    },
+}
+
+impl ImageSource {
+    /// Create a new base64 [`ImageSource`].
+    ///
+    /// The media type is normalized with [`normalize_media_type`], so aliases
+    /// such as `image/jpg` are replaced by their canonical form. Use
+    /// [`ContentBlock::image_base64`] to also check the image data.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - The media type of the image, e.g. `image/png`.
+    /// * `data` - The base64 encoded image data.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the new [`ImageSource`] on success, or an
+    /// [`AnthropicError::Validation`] if the media type is not supported.
+    pub fn base64(media_type: impl AsRef<str>, data: impl Into<String>) -> Result<Self> {
+        Ok(Self::Base64 {
+            media_type: normalize_media_type(media_type.as_ref())?.to_owned(),
+            data: data.into(),
+        })
+    }
+
+    /// Create a new URL [`ImageSource`].
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the image.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ImageSource`].
+    #[inline(always)]
+    pub fn url(url: impl Into<String>) -> Self {
+        Self::Url { url: url.into() }
+    }
+
+    /// Build the image content block for this source.
+    fn to_block(&self) -> Value {
+        json!({ "type": "image", "source": self })
+    }
+}
+
+/// Enum representing a content block of a message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    /// Plain text.
+    Text {
+        /// The text of the block.
+        text: String,
+    },
+
+    /// An image.
+    Image {
+        /// Source of the image data.
+        source: ImageSource,
+    },
+
+    /// Reasoning of the model, when extended thinking is enabled.
+    ///
+    /// Must be sent back unchanged in following turns, the API verifying the
+    /// thinking against its signature.
+    Thinking {
+        /// The reasoning text.
+        thinking: String,
+
+        /// Opaque signature of the reasoning, kept verbatim.
+        ///
+        /// Empty while the block is streamed, until its signature arrives.
+        #[serde(default)]
+        signature: String,
+    },
+
+    /// Reasoning of the model encrypted for safety reasons.
+    RedactedThinking {
+        /// Opaque encrypted reasoning, kept verbatim.
+        data: String,
+    },
+
+    /// A tool call made by the model.
+    ToolUse(ToolUse),
+
+    /// The result of a tool call, sent back by the user.
+    ToolResult {
+        /// Id of the `tool_use` block this is the result of.
+        tool_use_id: String,
+
+        /// Output of the tool, or the error message if the call failed.
+        content: String,
+
+        /// Whether the tool call failed.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+
+    /// Any block of a type not yet known to this crate, e.g. the result of a
+    /// server tool, kept as raw JSON so that it is sent back unchanged.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+/// Struct representing a tool call made by the model.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolUse {
+    /// Id of the call, referenced by the matching tool result.
+    pub id: String,
+
+    /// Name of the tool to call.
+    pub name: String,
+
+    /// Input of the call, following the input schema of the tool.
+    pub input: Value,
+}
+
+impl ToolUse {
+    /// Parse the tool calls of a list of raw content blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content blocks of a message.
+    ///
+    /// # Returns
+    ///
+    /// The `tool_use` blocks of the content, in order.
+    pub fn parse_all(content: &[Value]) -> Vec<Self> {
+        content
+            .iter()
+            .filter(|block| block["type"] == "tool_use")
+            .filter_map(|block| Self::deserialize(block).ok())
+            .collect()
+    }
+}
+
+impl ContentBlock {
+    /// Create a new text [`ContentBlock`].
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the block.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ContentBlock`].
+    #[inline(always)]
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// Create a new image [`ContentBlock`].
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source of the image data.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ContentBlock`].
+    #[inline(always)]
+    pub fn image(source: ImageSource) -> Self {
+        Self::Image { source }
+    }
+
+    /// Create a new image [`ContentBlock`] from base64 data, validating it.
+    ///
+    /// Corrupt image data otherwise only surfaces as an opaque error from the
+    /// API, so the data is checked to be well-formed base64 and to start with
+    /// the magic bytes of the declared media type before anything is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - The media type of the image, one of `image/jpeg`,
+    ///   `image/png`, `image/gif`, or `image/webp`, normalized with
+    ///   [`normalize_media_type`].
+    /// * `data` - The base64 encoded image data.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the new [`ContentBlock`] on success, or an
+    /// [`AnthropicError::Validation`] describing the mismatch, e.g. `declared
+    /// image/png but bytes look like JPEG`.
+    pub fn image_base64(media_type: impl AsRef<str>, data: impl Into<String>) -> Result<Self> {
+        let media_type = normalize_media_type(media_type.as_ref())?;
+        let data = data.into();
+        check_image(media_type, &data)?;
+
+        ImageSource::base64(media_type, data).map(Self::image)
+    }
+
+    /// Create a new image [`ContentBlock`] from raw image bytes, validating them.
+    ///
+    /// The bytes are base64 encoded and checked as by
+    /// [`ContentBlock::image_base64`].
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - The media type of the image, e.g. `image/png`.
+    /// * `bytes` - The raw image data, e.g. as read from a file.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the new [`ContentBlock`] on success, or an
+    /// [`AnthropicError::Validation`] describing the mismatch.
+    #[inline(always)]
+    pub fn image_bytes(media_type: impl AsRef<str>, bytes: impl AsRef<[u8]>) -> Result<Self> {
+        Self::image_base64(media_type, encode_base64(bytes.as_ref()))
+    }
+
+    /// Create a new [`ContentBlock`] holding the result of a successful tool call.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_use_id` - The id of the `tool_use` block being answered.
+    /// * `content` - The output of the tool.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ContentBlock`].
+    #[inline(always)]
+    pub fn tool_result(tool_use_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self::ToolResult {
+            tool_use_id: tool_use_id.into(),
+            content: content.into(),
+            is_error: false,
+        }
+    }
+
+    /// Create a new [`ContentBlock`] reporting that a tool call failed.
+    ///
+    /// The block is flagged with `is_error`, which tells the model the call did
+    /// not produce a result so it can retry or work around the failure instead
+    /// of treating the message as the tool output.
+    ///
+    /// # Arguments
+    ///
+    /// * `tool_use_id` - The id of the `tool_use` block being answered.
+    /// * `message` - A description of the failure.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ContentBlock`].
+    #[inline(always)]
+    pub fn tool_result_error(tool_use_id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::ToolResult {
+            tool_use_id: tool_use_id.into(),
+            content: message.into(),
+            is_error: true,
+        }
+    }
+
+    /// Truncate the text of the block to an estimated number of tokens.
+    ///
+    /// Meant for large tool outputs, e.g. a whole file, which would otherwise
+    /// fill the context window when sent back to the model. The text of text
+    /// and tool result blocks is cut with [`truncate_to_tokens`], ending with
+    /// a truncation marker; other blocks are returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_tokens` - The estimated number of tokens to stay within.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`ContentBlock`].
+    pub fn truncated(mut self, max_tokens: u64) -> Self {
+        if let Self::Text { text } | Self::ToolResult { content: text, .. } = &mut self {
+            if let Cow::Owned(truncated) = truncate_to_tokens(text, max_tokens) {
+                *text = truncated;
+            }
+        }
+        self
+    }
+}
+
+impl From<ContentBlock> for Value {
+    #[inline(always)]
+    fn from(block: ContentBlock) -> Self {
+        serde_json::to_value(block).unwrap_or_default()
+    }
+}
+
+/// Struct representing a single message in a conversation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    /// Role of the message author.
+    pub role: Role,
+
+    /// Content blocks of the message.
+    pub content: Vec<Value>,
+}
+
+impl Message {
+    /// Create a new user [`Message`] containing a single text block.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Message`].
+    #[inline(always)]
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: vec![text_block(text.into())],
+        }
+    }
+
+    /// Create a new assistant [`Message`] containing a single text block.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the message.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Message`].
+    #[inline(always)]
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: vec![text_block(text.into())],
+        }
+    }
+
+    /// Create a new user [`Message`] asking about several images.
+    ///
+    /// The images are placed before the text, which is the ordering recommended
+    /// for vision prompts.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The prompt referring to the images, e.g. "Compare these images.".
+    /// * `images` - The images to include, in order.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Message`].
+    pub fn user_with_images(text: impl Into<String>, images: Vec<ImageSource>) -> Self {
+        let mut content: Vec<Value> = images.iter().map(ImageSource::to_block).collect();
+        content.push(text_block(text.into()));
+
+        Self {
+            role: Role::User,
+            content,
+        }
+    }
+
+    /// Create a new user [`Message`] asking about several numbered images.
+    ///
+    /// Each image is preceded by a `Image N:` text label so the prompt can refer
+    /// to the images by number, and the text is placed after all images.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The prompt referring to the images, e.g. "How is Image 2 different?".
+    /// * `images` - The images to include, numbered from 1 in order.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Message`].
+    pub fn user_with_labeled_images(text: impl Into<String>, images: Vec<ImageSource>) -> Self {
+        let mut content = Vec::with_capacity(images.len() * 2 + 1);
+        for (index, image) in images.iter().enumerate() {
+            content.push(text_block(format!("Image {}:", index + 1)));
+            content.push(image.to_block());
+        }
+        content.push(text_block(text.into()));
+
+        Self {
+            role: Role::User,
+            content,
+        }
+    }
+
+    /// Create a new user [`Message`] from content blocks, e.g. tool results.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - The content blocks of the message, in order.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Message`].
+    pub fn user_blocks(blocks: Vec<ContentBlock>) -> Self {
+        Self {
+            role: Role::User,
+            content: blocks.into_iter().map(Value::from).collect(),
+        }
+    }
+
+    /// Move the images of a user message before its text.
+    ///
+    /// See [`MessageRequest::images_first`] for how blocks are reordered.
+    /// Assistant messages are returned unchanged.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`Message`].
+    ///
+    /// [`MessageRequest::images_first`]: super::messages::MessageRequest::images_first
+    pub fn images_first(mut self) -> Self {
+        if self.role == Role::User {
+            images_first(&mut self.content);
+        }
+        self
+    }
+}
+
+impl From<Message> for Value {
+    #[inline(always)]
+    fn from(message: Message) -> Self {
+        json!({ "role": message.role, "content": message.content })
+    }
+}
+
+/// [`MessageBuilder`] struct to build a message block by block.
+///
+/// Blocks are kept in the order they are added. The first invalid image is
+/// reported by [`MessageBuilder::build`], so calls can be chained without
+/// checking each of them.
+#[derive(Debug)]
+pub struct MessageBuilder {
+    /// Role of the message author.
+    role: Role,
+
+    /// Content blocks added so far.
+    content: Vec<Value>,
+
+    /// First error met while adding blocks, if any.
+    error: Option<AnthropicError>,
+}
+
+impl MessageBuilder {
+    /// Create a new [`MessageBuilder`] for a user message.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`MessageBuilder`] without any block.
+    #[inline(always)]
+    pub fn user() -> Self {
+        Self::new(Role::User)
+    }
+
+    /// Create a new [`MessageBuilder`] for an assistant message, e.g. a prefill.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`MessageBuilder`] without any block.
+    #[inline(always)]
+    pub fn assistant() -> Self {
+        Self::new(Role::Assistant)
+    }
+
+    /// Create a new [`MessageBuilder`] for `role`.
+    #[inline(always)]
+    fn new(role: Role) -> Self {
+        Self {
+            role,
+            content: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Add a text block.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the block.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageBuilder`].
+    #[inline(always)]
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.block(Ok(ContentBlock::text(text)))
+    }
+
+    /// Add an image block from raw image bytes, base64 encoded internally.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - The media type of the image, e.g. `image/png`.
+    /// * `bytes` - The raw image data, e.g. as read from a file.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageBuilder`].
+    #[inline(always)]
+    pub fn image_bytes(self, media_type: impl AsRef<str>, bytes: impl AsRef<[u8]>) -> Self {
+        self.block(ContentBlock::image_bytes(media_type, bytes))
+    }
+
+    /// Add an image block from already base64 encoded data.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_type` - The media type of the image, e.g. `image/png`.
+    /// * `data` - The base64 encoded image data.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageBuilder`].
+    #[inline(always)]
+    pub fn image_base64(self, media_type: impl AsRef<str>, data: impl Into<String>) -> Self {
+        self.block(ContentBlock::image_base64(media_type, data))
+    }
+
+    /// Add an image block referencing an image by URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the image.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageBuilder`].
+    #[inline(always)]
+    pub fn image_url(self, url: impl Into<String>) -> Self {
+        self.block(Ok(ContentBlock::image(ImageSource::url(url))))
+    }
+
+    /// Mark the last block added as the end of a cached prefix.
+    ///
+    /// See [`cache_control`](super::cache::cache_control). Does nothing if no
+    /// block was added yet.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageBuilder`].
+    pub fn cache_control(mut self) -> Self {
+        if let Some(block) = self.content.last_mut() {
+            cache::mark_breakpoint(block);
+        }
+        self
+    }
+
+    /// Add a block, or keep the error if it is the first one.
+    fn block(mut self, block: Result<ContentBlock>) -> Self {
+        match block {
+            Ok(block) => self.content.push(block.into()),
+            Err(error) => {
+                self.error.get_or_insert(error);
+            },
+        }
+        self
+    }
+
+    /// Build the [`Message`].
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`Message`] on success, or the
+    /// [`AnthropicError::Validation`] of the first invalid image.
+    pub fn build(self) -> Result<Message> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(Message {
+                role: self.role,
+                content: self.content,
+            }),
+        }
+    }
+}
+
+/// Struct representing a user message placing text before an image.
+///
+/// Images give the best results when placed before the text referring to
+/// them, so such messages are reported by
+/// [`MessageRequest::image_order_warnings`].
+///
+/// [`MessageRequest::image_order_warnings`]: super::messages::MessageRequest::image_order_warnings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageOrderWarning {
+    /// Index of the message in the request.
+    pub message: usize,
+
+    /// Index of the first text block placed before an image.
+    pub text_block: usize,
+
+    /// Index of the first image block placed after that text.
+    pub image_block: usize,
+}
+
+impl fmt::Display for ImageOrderWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "message {}: text block {} precedes image block {}, images work best before the text \
+             referring to them",
+            self.message, self.text_block, self.image_block
+        )
+    }
+}
+
+/// Find the first text block of a message placed before one of its images.
+///
+/// # Returns
+///
+/// The indices of the text block and of the first image after it, or `None`
+/// if every image comes before the text.
+pub(crate) fn text_before_image(content: &[Value]) -> Option<(usize, usize)> {
+    let text = (0..content.len())
+        .find(|&index| content[index]["type"] == "text" && !is_image_label(content, index))?;
+    let image = content[text..]
+        .iter()
+        .position(|block| block["type"] == "image")?;
+
+    Some((text, text + image))
+}
+
+/// Move the images of a message before its text, keeping their labels.
+///
+/// Only text and image blocks move, each group keeping its order; other
+/// blocks such as tool results keep their place.
+pub(crate) fn images_first(content: &mut [Value]) {
+    let slots: Vec<usize> = (0..content.len())
+        .filter(|&index| matches!(content[index]["type"].as_str(), Some("text" | "image")))
+        .collect();
+    let (images, texts): (Vec<usize>, Vec<usize>) = slots
+        .iter()
+        .partition(|&&index| content[index]["type"] == "image" || is_image_label(content, index));
+
+    let blocks: Vec<Value> = images
+        .iter()
+        .chain(&texts)
+        .map(|&index| content[index].take())
+        .collect();
+    for (slot, block) in slots.into_iter().zip(blocks) {
+        content[slot] = block;
+    }
+}
+
+/// Whether a block is a text label of the image following it, e.g. `Image 1:`.
+fn is_image_label(content: &[Value], index: usize) -> bool {
+    content[index]["type"] == "text"
+        && content[index]["text"]
+            .as_str()
+            .is_some_and(|text| text.trim_end().ends_with(':'))
+        && content
+            .get(index + 1)
+            .is_some_and(|block| block["type"] == "image")
+}
+
+/// Build a text content block.
+#[inline(always)]
+fn text_block(text: String) -> Value {
+    json!({ "type": "text", "text": text })
+}
+
+/// Normalize the media type of an image to the form the API accepts.
+///
+/// The API only accepts the exact canonical media types, so minor variations
+/// are a common cause of rejected requests. Case and surrounding whitespace
+/// are ignored, parameters such as `; charset=binary` are dropped, and common
+/// aliases such as `image/jpg` are mapped to their canonical form.
+///
+/// # Arguments
+///
+/// * `media_type` - The media type to normalize.
+///
+/// # Returns
+///
+/// A [`Result`] containing the canonical media type on success, or an
+/// [`AnthropicError::Validation`] listing the supported media types.
+pub fn normalize_media_type(media_type: &str) -> Result<&'static str> {
+    let essence = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    IMAGE_FORMATS
+        .iter()
+        .map(|format| format.media_type)
+        .find(|canonical| *canonical == essence)
+        .or_else(|| {
+            MEDIA_TYPE_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == essence)
+                .map(|(_, canonical)| *canonical)
+        })
+        .ok_or_else(|| unsupported_media_type(media_type))
+}
+
+/// Build the error for a media type the API does not accept.
+fn unsupported_media_type(media_type: &str) -> AnthropicError {
+    let supported: Vec<&str> = IMAGE_FORMATS
+        .iter()
+        .map(|format| format.media_type)
+        .collect();
+
+    AnthropicError::Validation(format!(
+        "unsupported image media type {media_type:?}, expected one of {}",
+        supported.join(", ")
+    ))
+}
+
+/// Check that base64 image data is well-formed and matches its media type.
+fn check_image(media_type: &str, data: &str) -> Result<()> {
+    let Some(format) = IMAGE_FORMATS
+        .iter()
+        .find(|format| format.media_type == media_type)
+    else {
+        return Err(unsupported_media_type(media_type));
+    };
+
+    let header = decode_base64_header(data).ok_or_else(|| {
+        AnthropicError::Validation(format!(
+            "image data declared as {media_type} is not valid base64"
+        ))
+    })?;
+    if (format.matches)(&header) {
+        return Ok(());
+    }
+
+    let message = match IMAGE_FORMATS
+        .iter()
+        .find(|format| (format.matches)(&header))
+    {
+        Some(actual) => format!("declared {media_type} but bytes look like {}", actual.name),
+        None => format!("declared {media_type} but bytes are not a recognized image format"),
+    };
+    Err(AnthropicError::Validation(message))
+}
+
+/// Encode bytes as standard base64 with padding.
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Validate standard base64 with padding and decode its first bytes.
+///
+/// The whole string is checked, but only the first 12 decoded bytes, enough to
+/// identify an image format, are returned.
+fn decode_base64_header(data: &str) -> Option<Vec<u8>> {
+    let sextet = |byte: u8| match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+
+    let bytes = data.as_bytes();
+    if bytes.is_empty() || !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let payload = bytes
+        .strip_suffix(b"==")
+        .or_else(|| bytes.strip_suffix(b"="));
+    let payload = payload.unwrap_or(bytes);
+    let mut header = Vec::with_capacity(12);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for &byte in payload {
+        bits = (bits << 6) | u32::from(sextet(byte)?);
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            if header.len() < 12 {
+                header.push((bits >> bit_count) as u8);
+            }
+        }
+    }
+
+    Some(header)
+}
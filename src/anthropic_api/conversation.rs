@@ -0,0 +1,689 @@
+use super::{
+    client::AnthropicClient,
+    content::{ContentBlock, Message, Role, ToolUse},
+    error::{AnthropicError, Result},
+    messages::{Container, MessageRequest, MessageResponse, StopReason},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{fs, path::Path};
+
+/// Default number of times [`Conversation::send`] resends a paused turn.
+pub const DEFAULT_MAX_CONTINUATIONS: usize = 10;
+
+/// Version of the transcript format written by [`Conversation::save`].
+///
+/// Transcripts without a version are read as version `0`. Fields added by
+/// later versions are optional when loading, so older transcripts still load
+/// with those fields set to their defaults.
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// Enum representing how prefills ending in whitespace are handled.
+///
+/// The API rejects an assistant prefill that ends in whitespace with a generic
+/// `400` error, so such prefills are caught before sending.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefillWhitespace {
+    /// Fail with [`AnthropicError::Validation`] explaining the constraint.
+    #[default]
+    Reject,
+
+    /// Remove the trailing whitespace and send the trimmed prefill.
+    Trim,
+}
+
+/// [`Conversation`] struct holding the running history of a multi-turn chat.
+pub struct Conversation {
+    /// Model name to be used for generating messages.
+    model: String,
+
+    /// Maximum number of tokens to generate per request.
+    max_tokens: u64,
+
+    /// System prompt sent with every request.
+    system: Option<String>,
+
+    /// History of messages in the conversation.
+    messages: Vec<Value>,
+
+    /// Maximum number of automatic resends when a turn is paused.
+    max_continuations: usize,
+
+    /// Id of the code execution container reused across turns.
+    container: Option<String>,
+
+    /// How prefills ending in whitespace are handled.
+    prefill_whitespace: PrefillWhitespace,
+
+    /// Definitions of the tools the model may call.
+    tools: Vec<Value>,
+
+    /// Whether the last message is an assistant turn the next reply continues,
+    /// i.e. a prefill appended with [`Conversation::push`] or a paused turn.
+    open_turn: bool,
+}
+
+impl Conversation {
+    /// Create a new instance of [`Conversation`].
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model name to use for generating messages.
+    /// * `max_tokens` - The maximum number of tokens to generate per request.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Conversation`] with an empty history.
+    #[inline(always)]
+    pub fn new(model: String, max_tokens: u64) -> Self {
+        Self {
+            model,
+            max_tokens,
+            system: None,
+            messages: Vec::new(),
+            max_continuations: DEFAULT_MAX_CONTINUATIONS,
+            container: None,
+            prefill_whitespace: PrefillWhitespace::default(),
+            tools: Vec::new(),
+            open_turn: false,
+        }
+    }
+
+    /// Set the system prompt sent with every request.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system prompt.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`Conversation`].
+    #[inline(always)]
+    pub fn system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    /// Set the maximum number of automatic resends for a paused turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_continuations` - How many times a `pause_turn` response is resent.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`Conversation`].
+    #[inline(always)]
+    pub fn max_continuations(mut self, max_continuations: usize) -> Self {
+        self.max_continuations = max_continuations;
+        self
+    }
+
+    /// Set how prefills ending in whitespace are handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefill_whitespace` - Whether to reject or trim such prefills.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`Conversation`].
+    #[inline(always)]
+    pub fn prefill_whitespace(mut self, prefill_whitespace: PrefillWhitespace) -> Self {
+        self.prefill_whitespace = prefill_whitespace;
+        self
+    }
+
+    /// Set the tools the model may call.
+    ///
+    /// # Arguments
+    ///
+    /// * `tools` - The tool definitions, each with a `name`, `description`, and `input_schema`.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`Conversation`].
+    #[inline(always)]
+    pub fn tools(mut self, tools: Vec<Value>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Get the history of messages in the conversation.
+    #[inline(always)]
+    pub fn messages(&self) -> &[Value] {
+        &self.messages
+    }
+
+    /// Get the id of the code execution container used by the conversation.
+    ///
+    /// The id is taken from the latest response that reported a container and
+    /// is sent with every following request so files persist across turns.
+    #[inline(always)]
+    pub fn container(&self) -> Option<&str> {
+        self.container.as_deref()
+    }
+
+    /// Save the conversation to a JSON transcript file.
+    ///
+    /// The transcript holds the model, system prompt, history, and settings,
+    /// tagged with [`TRANSCRIPT_VERSION`], so the session can be resumed later
+    /// with [`Conversation::load`]. Tool definitions are not saved since they
+    /// go along with the code executing the tools; set them again after
+    /// loading.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file to write, replaced if it exists.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] which is empty on success, or an [`AnthropicError`] if the
+    /// file could not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let transcript = Transcript {
+            version: TRANSCRIPT_VERSION,
+            model: self.model.clone(),
+            max_tokens: self.max_tokens,
+            system: self.system.clone(),
+            messages: self.messages.clone(),
+            max_continuations: self.max_continuations,
+            container: self.container.clone(),
+            prefill_whitespace: self.prefill_whitespace,
+            open_turn: self.open_turn,
+        };
+
+        fs::write(path, serde_json::to_vec_pretty(&transcript)?)?;
+        Ok(())
+    }
+
+    /// Load a conversation from a JSON transcript file.
+    ///
+    /// Transcripts written by older versions of this crate are accepted, with
+    /// the fields they lack set to their defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of a file written by [`Conversation::save`].
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the restored [`Conversation`] on success, or an
+    /// [`AnthropicError`] if the file could not be read, is not a transcript,
+    /// or was written by a newer, unsupported version.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let transcript: Transcript = serde_json::from_slice(&fs::read(path)?)?;
+        if transcript.version > TRANSCRIPT_VERSION {
+            return Err(AnthropicError::Validation(format!(
+                "transcript version {} is newer than the supported version {TRANSCRIPT_VERSION}",
+                transcript.version
+            )));
+        }
+
+        Ok(Self {
+            model: transcript.model,
+            max_tokens: transcript.max_tokens,
+            system: transcript.system,
+            messages: transcript.messages,
+            max_continuations: transcript.max_continuations,
+            container: transcript.container,
+            prefill_whitespace: transcript.prefill_whitespace,
+            tools: Vec::new(),
+            open_turn: transcript.open_turn,
+        })
+    }
+
+    /// Append a user message to the conversation.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text content of the user message.
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the [`Conversation`].
+    pub fn user(&mut self, text: impl Into<String>) -> &mut Self {
+        self.messages
+            .push(json!({ "role": "user", "content": text.into() }));
+        self.open_turn = false;
+        self
+    }
+
+    /// Append a message to the conversation, e.g. to replay earlier history.
+    ///
+    /// An assistant message appended last is a prefill: the next reply
+    /// continues it and is merged into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to append.
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the [`Conversation`].
+    pub fn push(&mut self, message: Message) -> &mut Self {
+        self.open_turn = message.role == Role::Assistant;
+        self.messages.push(message.into());
+        self
+    }
+
+    /// Get the tool calls of the last assistant message still awaiting a result.
+    ///
+    /// # Returns
+    ///
+    /// The pending [`ToolUse`] blocks, in the order the model made them.
+    pub fn pending_tool_uses(&self) -> Vec<ToolUse> {
+        let Some(last) = self
+            .messages
+            .iter()
+            .rposition(|message| message["role"] == "assistant")
+        else {
+            return Vec::new();
+        };
+
+        let answered: Vec<&str> = self.messages[last + 1..]
+            .iter()
+            .filter_map(|message| message["content"].as_array())
+            .flatten()
+            .filter(|block| block["type"] == "tool_result")
+            .filter_map(|block| block["tool_use_id"].as_str())
+            .collect();
+
+        let content = self.messages[last]["content"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        ToolUse::parse_all(content)
+            .into_iter()
+            .filter(|tool_use| !answered.contains(&tool_use.id.as_str()))
+            .collect()
+    }
+
+    /// Append the result of a tool call to the conversation.
+    ///
+    /// The result must answer one of the [`Conversation::pending_tool_uses`],
+    /// matched by id, so that when the model makes several calls at once each
+    /// result is correlated with the right call. Results of the same turn are
+    /// gathered into a single user message, as the API expects.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - A [`ContentBlock::ToolResult`], e.g. built with
+    ///   [`ContentBlock::tool_result`] or [`ContentBlock::tool_result_error`].
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing a mutable reference to the [`Conversation`] on
+    /// success, or an [`AnthropicError::Validation`] if the block is not a tool
+    /// result or its id does not match a pending tool call.
+    pub fn add_tool_result(&mut self, result: ContentBlock) -> Result<&mut Self> {
+        let ContentBlock::ToolResult { tool_use_id, .. } = &result else {
+            return Err(AnthropicError::Validation(
+                "add_tool_result expects a tool_result block".into(),
+            ));
+        };
+        if !self
+            .pending_tool_uses()
+            .iter()
+            .any(|tool_use| tool_use.id == *tool_use_id)
+        {
+            return Err(AnthropicError::Validation(format!(
+                "tool_use_id {tool_use_id:?} does not match a pending tool call"
+            )));
+        }
+
+        let block = Value::from(result);
+        if let Some(last) = self.messages.last_mut() {
+            let is_tool_results = last["role"] == "user"
+                && last["content"].as_array().is_some_and(|blocks| {
+                    blocks.iter().all(|block| block["type"] == "tool_result")
+                });
+            if is_tool_results {
+                if let Some(blocks) = last["content"].as_array_mut() {
+                    blocks.push(block);
+                    return Ok(self);
+                }
+            }
+        }
+
+        self.messages
+            .push(json!({ "role": "user", "content": [block] }));
+        self.open_turn = false;
+        Ok(self)
+    }
+
+    /// Send the conversation and record the assistant reply in the history.
+    ///
+    /// When the API answers with [`StopReason::PauseTurn`], the partial reply is
+    /// appended as the last assistant message and the history is resent as-is,
+    /// which tells the API to continue the same turn. Content from every
+    /// continuation is merged into that single assistant message. This repeats
+    /// up to the configured number of continuations; if the turn is still
+    /// paused afterwards, the paused response is returned and calling `send`
+    /// again resumes it.
+    ///
+    /// A reply that fails to deserialize is not recorded, so the history is
+    /// left as it was before the request that returned it.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`AnthropicClient`] used to send the request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageResponse`] of the last request on
+    /// success, or an [`AnthropicError`] on failure.
+    pub async fn send(&mut self, client: &AnthropicClient) -> Result<MessageResponse> {
+        self.send_parsed(client, |response| {
+            Ok(client.deserialize_value(response.clone())?)
+        })
+        .await
+    }
+
+    /// Send the conversation and return the raw JSON response of the last request.
+    ///
+    /// The reply is recorded and paused turns are continued as with
+    /// [`Conversation::send`]. Use this to read fields [`MessageResponse`]
+    /// does not model yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`AnthropicClient`] used to send the request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the JSON response of the last request on success,
+    /// or an [`AnthropicError`] on failure, e.g. an
+    /// [`AnthropicError::Validation`] if the history ends with a complete
+    /// assistant reply.
+    pub async fn send_raw(&mut self, client: &AnthropicClient) -> Result<Value> {
+        self.send_parsed(client, |response| Ok(response.clone()))
+            .await
+    }
+
+    /// Send the conversation, recording every reply once `parse` accepted it.
+    async fn send_parsed<T>(
+        &mut self,
+        client: &AnthropicClient,
+        parse: impl Fn(&Value) -> Result<T>,
+    ) -> Result<T> {
+        self.check_turn()?;
+        let mut continuations = 0;
+
+        loop {
+            let response = client.messages().create_raw(self.request()).await?;
+            let parsed = parse(&response)?;
+            self.record_container(Container::deserialize(&response["container"]).ok().as_ref());
+            let stop_reason = stop_reason(&response);
+            self.push_assistant(&response, stop_reason);
+
+            if stop_reason != Some(StopReason::PauseTurn) || continuations >= self.max_continuations
+            {
+                return Ok(parsed);
+            }
+
+            continuations += 1;
+        }
+    }
+
+    /// Send the conversation with the assistant reply prefilled.
+    ///
+    /// The prefill is sent as the start of the assistant turn and the model
+    /// continues from it. The API does not echo the prefill back, so it is
+    /// prepended to the generated text before being returned. The reply is
+    /// recorded in the history as a single assistant message with every block
+    /// of the response, the prefill merged into its first text block.
+    ///
+    /// The API does not accept a prefill ending in whitespace; depending on
+    /// [`Conversation::prefill_whitespace`] such a prefill is either rejected
+    /// before sending or trimmed.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`AnthropicClient`] used to send the request.
+    /// * `prefill` - The text the assistant reply must start with.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the full assistant text, prefill included, on
+    /// success, or an [`AnthropicError`] on failure.
+    pub async fn send_with_prefill(
+        &mut self,
+        client: &AnthropicClient,
+        prefill: &str,
+    ) -> Result<String> {
+        self.check_turn()?;
+        let prefill = self.check_prefill(prefill)?;
+        self.messages
+            .push(json!({ "role": "assistant", "content": prefill }));
+        let response = client.messages().create(self.request()).await;
+        self.messages.pop();
+
+        let response = response?;
+        self.record_container(response.container.as_ref());
+
+        let mut content: Vec<Value> = response.content.iter().cloned().map(Value::from).collect();
+        match content.iter_mut().find(|block| block["type"] == "text") {
+            Some(block) => {
+                let generated = block["text"].as_str().unwrap_or_default();
+                block["text"] = Value::String(format!("{prefill}{generated}"));
+            },
+            None => content.insert(0, json!({ "type": "text", "text": prefill })),
+        }
+        self.messages
+            .push(json!({ "role": "assistant", "content": content }));
+        self.open_turn = response.stop_reason == Some(StopReason::PauseTurn);
+
+        Ok(format!("{prefill}{}", response.text()))
+    }
+
+    /// Send a user message and parse the reply as a JSON object.
+    ///
+    /// The assistant reply is prefilled with `{`, which steers the model into
+    /// answering with JSON only. The brace is not part of the generated text,
+    /// so it is added back before parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`AnthropicClient`] used to send the request.
+    /// * `text` - The text content of the user message.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the parsed JSON on success, or an
+    /// [`AnthropicError::InvalidJson`] holding the full reply if it is not
+    /// valid JSON.
+    pub async fn send_expecting_json(
+        &mut self,
+        client: &AnthropicClient,
+        text: impl Into<String>,
+    ) -> Result<Value> {
+        self.user(text);
+        let text = self.send_with_prefill(client, "{").await?;
+
+        serde_json::from_str(&text).map_err(|error| AnthropicError::InvalidJson { text, error })
+    }
+
+    /// Check that the history does not end with a complete assistant reply,
+    /// which the API would take as a prefill to continue.
+    fn check_turn(&self) -> Result<()> {
+        let ends_with_reply = self
+            .messages
+            .last()
+            .is_some_and(|last| last["role"] == "assistant");
+        if ends_with_reply && !self.open_turn {
+            return Err(AnthropicError::Validation(
+                "the conversation ends with a complete assistant reply; append a user message \
+                 before sending"
+                    .into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Apply the trailing whitespace policy to a prefill.
+    fn check_prefill<'p>(&self, prefill: &'p str) -> Result<&'p str> {
+        let trimmed = prefill.trim_end();
+        if trimmed.len() == prefill.len() {
+            return Ok(prefill);
+        }
+
+        match self.prefill_whitespace {
+            PrefillWhitespace::Trim => Ok(trimmed),
+            PrefillWhitespace::Reject => Err(AnthropicError::Validation(format!(
+                "assistant prefill {prefill:?} ends in whitespace, which the API rejects; \
+                 remove the trailing whitespace or use PrefillWhitespace::Trim"
+            ))),
+        }
+    }
+
+    /// Build a request from the most recent turns of the history only.
+    ///
+    /// A turn starts with a user message and includes everything up to the
+    /// next one, so the request always starts with a user message and tool
+    /// calls stay together with their results. The system prompt, tools and
+    /// container are included as with a full request, and the conversation
+    /// itself is left untouched, e.g. to compare how much history to send.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of turns to keep, counted from the end.
+    ///
+    /// # Returns
+    ///
+    /// A [`MessageRequest`] for the last `n` turns, or for the whole history
+    /// if it has fewer.
+    pub fn request_for_last_n(&self, n: usize) -> MessageRequest {
+        let start = self
+            .messages
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, message)| is_turn_start(message))
+            .map(|(index, _)| index)
+            .take(n)
+            .last()
+            .unwrap_or(self.messages.len());
+
+        self.request_for(&self.messages[start..])
+    }
+
+    /// Build a request for the current history.
+    #[inline(always)]
+    fn request(&self) -> MessageRequest {
+        self.request_for(&self.messages)
+    }
+
+    /// Build a request for some of the messages of the history.
+    fn request_for(&self, messages: &[Value]) -> MessageRequest {
+        let mut request =
+            MessageRequest::new(self.model.clone(), messages.to_vec()).max_tokens(self.max_tokens);
+
+        if let Some(system) = &self.system {
+            request = request.system(system.as_str().into());
+        }
+        if !self.tools.is_empty() {
+            request = request.tools(self.tools.clone());
+        }
+        match &self.container {
+            Some(container) => request.container(container.clone()),
+            None => request,
+        }
+    }
+
+    /// Remember the container of a response for the following requests.
+    fn record_container(&mut self, container: Option<&Container>) {
+        if let Some(container) = container {
+            self.container = Some(container.id.clone());
+        }
+    }
+
+    /// Record the content of a response as the assistant turn, extending the
+    /// last assistant message if it is a prefill or a paused turn.
+    fn push_assistant(&mut self, response: &Value, stop_reason: Option<StopReason>) {
+        let content = match &response["content"] {
+            Value::Array(blocks) => blocks.clone(),
+            _ => Vec::new(),
+        };
+        let open_turn = std::mem::replace(
+            &mut self.open_turn,
+            stop_reason == Some(StopReason::PauseTurn),
+        );
+
+        if let Some(last) = self.messages.last_mut() {
+            if open_turn && last["role"] == "assistant" {
+                if let Value::String(text) = &last["content"] {
+                    last["content"] = json!([{ "type": "text", "text": text }]);
+                }
+                if let Some(blocks) = last["content"].as_array_mut() {
+                    blocks.extend(content);
+                    return;
+                }
+            }
+        }
+
+        self.messages
+            .push(json!({ "role": "assistant", "content": content }));
+    }
+}
+
+/// Struct representing the on-disk transcript of a [`Conversation`].
+#[derive(Deserialize, Serialize)]
+struct Transcript {
+    /// Version of the transcript format.
+    #[serde(default)]
+    version: u32,
+
+    /// Model name used for generating messages.
+    model: String,
+
+    /// Maximum number of tokens to generate per request.
+    max_tokens: u64,
+
+    /// System prompt sent with every request.
+    #[serde(default)]
+    system: Option<String>,
+
+    /// History of messages in the conversation.
+    messages: Vec<Value>,
+
+    /// Maximum number of automatic resends when a turn is paused.
+    #[serde(default = "default_max_continuations")]
+    max_continuations: usize,
+
+    /// Id of the code execution container reused across turns.
+    #[serde(default)]
+    container: Option<String>,
+
+    /// How prefills ending in whitespace are handled.
+    #[serde(default)]
+    prefill_whitespace: PrefillWhitespace,
+
+    /// Whether the last message is an assistant turn the next reply continues.
+    #[serde(default)]
+    open_turn: bool,
+}
+
+/// Default of [`Transcript::max_continuations`] for older transcripts.
+fn default_max_continuations() -> usize {
+    DEFAULT_MAX_CONTINUATIONS
+}
+
+/// Read the stop reason of a raw message response.
+fn stop_reason(response: &Value) -> Option<StopReason> {
+    StopReason::deserialize(&response["stop_reason"]).ok()
+}
+
+/// Whether a message starts a new turn, i.e. is a user message other than
+/// the answer to tool calls.
+fn is_turn_start(message: &Value) -> bool {
+    let answers_tool_calls = message["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|block| block["type"] == "tool_result");
+
+    message["role"] == "user" && !answers_tool_calls
+}
@@ -0,0 +1,240 @@
+use super::{
+    content::ContentBlock,
+    messages::{MessageResponse, StopReason, Usage},
+};
+use serde_json::Value;
+
+/// Struct representing a value that differs between two responses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change<T> {
+    /// The value in the response `diff` was called on.
+    pub left: T,
+
+    /// The value in the other response.
+    pub right: T,
+}
+
+/// Enum representing a line of the text diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineDiff {
+    /// The line is in both responses.
+    Same(String),
+
+    /// The line is only in the left response.
+    Removed(String),
+
+    /// The line is only in the right response.
+    Added(String),
+}
+
+/// Enum representing a difference between the tool calls of two responses.
+///
+/// Tool calls are compared by position; their ids are ignored since they
+/// differ on every request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToolCallDiff {
+    /// A tool call only in the left response.
+    Removed {
+        /// Position of the call among the tool calls of the left response.
+        index: usize,
+
+        /// Name of the tool.
+        name: String,
+
+        /// Input of the call.
+        input: Value,
+    },
+
+    /// A tool call only in the right response.
+    Added {
+        /// Position of the call among the tool calls of the right response.
+        index: usize,
+
+        /// Name of the tool.
+        name: String,
+
+        /// Input of the call.
+        input: Value,
+    },
+
+    /// The same tool called with a different input.
+    InputChanged {
+        /// Position of the call among the tool calls of both responses.
+        index: usize,
+
+        /// Name of the tool.
+        name: String,
+
+        /// Inputs of the call.
+        input: Change<Value>,
+    },
+}
+
+/// [`ResponseDiff`] struct holding the differences between two responses.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseDiff {
+    /// Line-based diff of the text content, including unchanged lines.
+    pub text: Vec<LineDiff>,
+
+    /// Differences between the tool calls.
+    pub tool_calls: Vec<ToolCallDiff>,
+
+    /// Models, if they differ.
+    pub model: Option<Change<String>>,
+
+    /// Stop reasons, if they differ.
+    pub stop_reason: Option<Change<Option<StopReason>>>,
+
+    /// Usages, if they differ.
+    pub usage: Option<Change<Usage>>,
+}
+
+impl ResponseDiff {
+    /// Whether the responses are equivalent, ignoring their ids.
+    pub fn is_empty(&self) -> bool {
+        !self.text_changed()
+            && self.tool_calls.is_empty()
+            && self.model.is_none()
+            && self.stop_reason.is_none()
+            && self.usage.is_none()
+    }
+
+    /// Whether the text content of the responses differs.
+    #[inline(always)]
+    pub fn text_changed(&self) -> bool {
+        !self
+            .text
+            .iter()
+            .all(|line| matches!(line, LineDiff::Same(_)))
+    }
+}
+
+impl MessageResponse {
+    /// Compare the response with another one, e.g. for evaluations across
+    /// models or prompt versions.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The response to compare with.
+    ///
+    /// # Returns
+    ///
+    /// A [`ResponseDiff`] from this response to `other`.
+    pub fn diff(&self, other: &MessageResponse) -> ResponseDiff {
+        ResponseDiff {
+            text: diff_lines(&self.text(), &other.text()),
+            tool_calls: diff_tool_calls(&tool_calls(self), &tool_calls(other)),
+            model: change(&self.model, &other.model),
+            stop_reason: change(&self.stop_reason, &other.stop_reason),
+            usage: change(&self.usage, &other.usage),
+        }
+    }
+}
+
+/// Build a [`Change`] if the values differ.
+fn change<T: Clone + PartialEq>(left: &T, right: &T) -> Option<Change<T>> {
+    (left != right).then(|| Change {
+        left: left.clone(),
+        right: right.clone(),
+    })
+}
+
+/// Get the name and input of the tool calls of a response.
+fn tool_calls(response: &MessageResponse) -> Vec<(String, Value)> {
+    response
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse(tool_use) => {
+                Some((tool_use.name.clone(), tool_use.input.clone()))
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Compare tool calls by position.
+fn diff_tool_calls(left: &[(String, Value)], right: &[(String, Value)]) -> Vec<ToolCallDiff> {
+    let mut diffs = Vec::new();
+
+    for index in 0..left.len().max(right.len()) {
+        match (left.get(index), right.get(index)) {
+            (Some((left_name, left_input)), Some((right_name, right_input)))
+                if left_name == right_name =>
+            {
+                if let Some(input) = change(left_input, right_input) {
+                    diffs.push(ToolCallDiff::InputChanged {
+                        index,
+                        name: left_name.clone(),
+                        input,
+                    });
+                }
+            },
+            (left, right) => {
+                if let Some((name, input)) = left {
+                    diffs.push(ToolCallDiff::Removed {
+                        index,
+                        name: name.clone(),
+                        input: input.clone(),
+                    });
+                }
+                if let Some((name, input)) = right {
+                    diffs.push(ToolCallDiff::Added {
+                        index,
+                        name: name.clone(),
+                        input: input.clone(),
+                    });
+                }
+            },
+        }
+    }
+
+    diffs
+}
+
+/// Diff two texts line by line, using their longest common subsequence.
+fn diff_lines(left: &str, right: &str) -> Vec<LineDiff> {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of
+    // left[i..] and right[j..].
+    let mut common = vec![vec![0usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            common[i][j] = if left[i] == right[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            lines.push(LineDiff::Same(left[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(LineDiff::Removed(left[i].to_owned()));
+            i += 1;
+        } else {
+            lines.push(LineDiff::Added(right[j].to_owned()));
+            j += 1;
+        }
+    }
+    lines.extend(
+        left[i..]
+            .iter()
+            .map(|line| LineDiff::Removed((*line).to_owned())),
+    );
+    lines.extend(
+        right[j..]
+            .iter()
+            .map(|line| LineDiff::Added((*line).to_owned())),
+    );
+
+    lines
+}
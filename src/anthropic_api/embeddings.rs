@@ -1,16 +1,37 @@
-use super::client::AnthropicClient;
+use super::{client::AnthropicClient, error::Result};
 use crate::setters;
-use serde::Serialize;
+use serde::{de::Error as _, Deserialize, Serialize};
 use serde_json::Value;
+use std::{borrow::Cow, collections::HashMap, hash::Hash};
 
 /// [`EmbeddingsApi`] struct to interact with the embeddings endpoint of the API.
-pub struct EmbeddingsApi<'a, 'b, 'c>(pub(crate) &'a AnthropicClient<'b, 'c>);
+pub struct EmbeddingsApi<'a>(pub(crate) &'a AnthropicClient);
+
+/// Struct representing the part of an embeddings response holding the vectors.
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    /// Embeddings, one per input.
+    data: Vec<Embedding>,
+}
+
+/// Struct representing the embedding of a single input.
+#[derive(Deserialize)]
+struct Embedding {
+    /// Position of the input in the request.
+    index: usize,
+
+    /// The embedding vector.
+    embedding: Vec<f32>,
+}
 
 /// Struct representing a request for embeddings.
+///
+/// Inputs are either owned or borrowed, so large in-memory corpora can be
+/// embedded without copying every string; see [`EmbeddingsRequest::new_borrowed`].
 #[derive(Default, Serialize)]
-pub struct EmbeddingsRequest {
+pub struct EmbeddingsRequest<'a> {
     /// Inputs for which embeddings need to be generated.
-    inputs: Vec<String>,
+    inputs: Vec<Cow<'a, str>>,
 
     /// Model name to be used for generating embeddings.
     model: String,
@@ -28,7 +49,7 @@ pub struct EmbeddingsRequest {
     encoding_format: Option<String>,
 }
 
-impl EmbeddingsRequest {
+impl EmbeddingsRequest<'static> {
     /// Create a new instance of [`EmbeddingsRequest`].
     ///
     /// # Arguments
@@ -43,7 +64,33 @@ impl EmbeddingsRequest {
     pub fn new(model: String, inputs: Vec<String>) -> Self {
         Self {
             model,
-            inputs,
+            inputs: inputs.into_iter().map(Cow::Owned).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> EmbeddingsRequest<'a> {
+    /// Create a new instance of [`EmbeddingsRequest`] borrowing its inputs.
+    ///
+    /// The inputs are serialized straight from the borrowed strings, avoiding
+    /// an owned copy of each one.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model name to use for generating embeddings.
+    /// * `inputs` - The input strings for which embeddings are to be generated.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`EmbeddingsRequest`].
+    pub fn new_borrowed<I>(model: String, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            model,
+            inputs: inputs.into_iter().map(Cow::Borrowed).collect(),
             ..Default::default()
         }
     }
@@ -58,7 +105,7 @@ impl EmbeddingsRequest {
         /// # Returns
         ///
         /// The updated instance of [`EmbeddingsRequest`].
-        input_type: String,
+        input_type: impl Into<String>,
 
         /// Set the truncation flag of the request.
         ///
@@ -80,11 +127,11 @@ impl EmbeddingsRequest {
         /// # Returns
         ///
         /// The updated instance of [`EmbeddingsRequest`].
-        encoding_format: String,
+        encoding_format: impl Into<String>,
     }
 }
 
-impl<'a> EmbeddingsApi<'a, '_, '_> {
+impl EmbeddingsApi<'_> {
     /// Create embeddings using the provided request parameters.
     ///
     /// # Arguments
@@ -94,9 +141,68 @@ impl<'a> EmbeddingsApi<'a, '_, '_> {
     /// # Returns
     ///
     /// A [`Result`] containing the JSON response as [`serde_json::Value`] on success,
-    /// or a [`serde_json::Error`] on failure.
-    pub async fn create(&self, request: EmbeddingsRequest) -> reqwest::Result<Value> {
+    /// or an [`AnthropicError`](super::error::AnthropicError) on failure.
+    pub async fn create(&self, request: EmbeddingsRequest<'_>) -> Result<Value> {
         // Send a POST request to the embeddings endpoint with the request body.
         self.0.post("/embeddings", &request).await
     }
+
+    /// Create embeddings for keyed inputs, e.g. documents by id.
+    ///
+    /// The inputs are embedded in a single request and the vectors are mapped
+    /// back to their keys. Identical strings are only sent once, and every key
+    /// they appear under receives the same vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model name to use for generating embeddings.
+    /// * `inputs` - The input strings, by key.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the embedding of every input by key on success,
+    /// or an [`AnthropicError`](super::error::AnthropicError) on failure,
+    /// including when the response does not hold one embedding per input.
+    pub async fn embed_map<K: Eq + Hash + Clone>(
+        &self,
+        model: String,
+        inputs: &HashMap<K, String>,
+    ) -> Result<HashMap<K, Vec<f32>>> {
+        let mut unique: Vec<&str> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let keys: Vec<(&K, usize)> = inputs
+            .iter()
+            .map(|(key, input)| {
+                let position = *positions.entry(input).or_insert_with(|| {
+                    unique.push(input);
+                    unique.len() - 1
+                });
+                (key, position)
+            })
+            .collect();
+
+        let count = unique.len();
+        let request = EmbeddingsRequest::new_borrowed(model, unique);
+        // Only the vectors are read, so strict mode does not apply to the rest.
+        let response: Value = self.0.post("/embeddings", &request).await?;
+        let response = EmbeddingsResponse::deserialize(response)?;
+
+        let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; count];
+        for embedding in response.data {
+            if let Some(slot) = embeddings.get_mut(embedding.index) {
+                *slot = Some(embedding.embedding);
+            }
+        }
+        let embeddings = embeddings
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                serde_json::Error::custom(format!("expected {count} embeddings in the response"))
+            })?;
+
+        Ok(keys
+            .into_iter()
+            .map(|(key, position)| (key.clone(), embeddings[position].clone()))
+            .collect())
+    }
 }
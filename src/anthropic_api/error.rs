@@ -0,0 +1,229 @@
+use super::audit::AuditError;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::{fmt, time::Duration};
+
+/// Result type returned by the API methods of this crate.
+pub type Result<T> = std::result::Result<T, AnthropicError>;
+
+/// [`AnthropicError`] enum representing the failures of an API call.
+#[derive(Debug)]
+pub enum AnthropicError {
+    /// The HTTP request failed or its body could not be read.
+    Http(reqwest::Error),
+
+    /// The API answered with an error status.
+    Api {
+        /// HTTP status of the response.
+        status: StatusCode,
+
+        /// Type of the error, e.g. `invalid_request_error`.
+        error_type: String,
+
+        /// Human readable error message.
+        message: String,
+
+        /// Request parameter the error is about, when the message names one,
+        /// e.g. `messages.0.content` for `messages.0.content: Field required`.
+        param: Option<String>,
+
+        /// Fields of the error object beyond its type and message, if any.
+        details: Option<Box<Value>>,
+
+        /// Full raw body of the response.
+        body: String,
+    },
+
+    /// A file could not be read or written.
+    Io(std::io::Error),
+
+    /// The response is not JSON, e.g. an HTML error page from a proxy.
+    UnexpectedContentType {
+        /// HTTP status of the response.
+        status: StatusCode,
+
+        /// The `Content-Type` of the response.
+        content_type: String,
+
+        /// The start of the response body.
+        snippet: String,
+    },
+
+    /// The response body could not be deserialized.
+    Deserialize(serde_json::Error),
+
+    /// The request was rejected locally before being sent.
+    Validation(String),
+
+    /// The model reply was expected to be JSON but could not be parsed.
+    InvalidJson {
+        /// The full text of the reply.
+        text: String,
+
+        /// The parse error.
+        error: serde_json::Error,
+    },
+
+    /// The audit hook failed, so the request was not sent.
+    Audit(AuditError),
+
+    /// A server-sent event exceeded the maximum frame size of the stream.
+    FrameTooLarge {
+        /// The maximum frame size, in bytes.
+        max_frame_size: usize,
+    },
+
+    /// The stream ended before the message was complete, e.g. because the
+    /// connection dropped.
+    IncompleteStream,
+
+    /// The client already has as many open streams as it allows.
+    StreamLimitReached,
+
+    /// The circuit breaker of the client is open, so the request was not sent.
+    CircuitOpen {
+        /// Time until the next trial request is let through.
+        retry_in: Duration,
+    },
+
+    /// A request, or an operation spanning several requests, did not complete
+    /// in time.
+    Timeout(Duration),
+
+    /// An environment variable configuring the client could not be read.
+    EnvVar {
+        /// Name of the variable.
+        name: &'static str,
+
+        /// Why it could not be read.
+        error: std::env::VarError,
+    },
+
+    /// The client is shutting down and no longer accepts new requests.
+    ShuttingDown,
+
+    /// The request was cancelled because the client shut down before it completed.
+    Cancelled,
+}
+
+impl fmt::Display for AnthropicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(error) => write!(f, "HTTP error: {error}"),
+            Self::Api {
+                status,
+                error_type,
+                message,
+                ..
+            } => write!(f, "API error {status} ({error_type}): {message}"),
+            Self::Io(error) => write!(f, "I/O error: {error}"),
+            Self::UnexpectedContentType {
+                status,
+                content_type,
+                snippet,
+            } => write!(
+                f,
+                "expected a JSON response but got {content_type} ({status}): {snippet}"
+            ),
+            Self::Deserialize(error) => write!(f, "failed to deserialize response: {error}"),
+            Self::Validation(message) => write!(f, "invalid request: {message}"),
+            Self::InvalidJson { error, .. } => write!(f, "reply is not valid JSON: {error}"),
+            Self::Audit(error) => write!(f, "audit hook failed: {error}"),
+            Self::FrameTooLarge { max_frame_size } => {
+                write!(f, "stream frame exceeds {max_frame_size} bytes")
+            },
+            Self::IncompleteStream => f.write_str("stream ended before the message was complete"),
+            Self::StreamLimitReached => f.write_str("concurrent stream limit reached"),
+            Self::CircuitOpen { retry_in } => write!(
+                f,
+                "circuit breaker open after repeated server errors, retry in {retry_in:?}"
+            ),
+            Self::Timeout(timeout) => write!(f, "operation did not complete within {timeout:?}"),
+            Self::EnvVar { name, error } => write!(f, "environment variable {name}: {error}"),
+            Self::ShuttingDown => f.write_str("client is shutting down"),
+            Self::Cancelled => f.write_str("request cancelled by client shutdown"),
+        }
+    }
+}
+
+impl std::error::Error for AnthropicError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(error) => Some(error),
+            Self::Io(error) => Some(error),
+            Self::Deserialize(error) => Some(error),
+            Self::InvalidJson { error, .. } => Some(error),
+            Self::Audit(error) => Some(error.as_ref()),
+            Self::EnvVar { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl AnthropicError {
+    /// Build the error for a response with an error status.
+    ///
+    /// Bodies that are not the usual JSON error object are kept as the message
+    /// so nothing is lost.
+    pub(crate) fn from_response(status: StatusCode, body: &[u8]) -> Self {
+        let body = String::from_utf8_lossy(body).into_owned();
+        let value: Value = serde_json::from_str(&body).unwrap_or_default();
+        let error = &value["error"];
+
+        let error_type = error["type"].as_str().unwrap_or("unknown").to_owned();
+        let message = error["message"]
+            .as_str()
+            .map(str::to_owned)
+            .unwrap_or_else(|| body.clone());
+        let param = error_param(&message);
+        let details = error.as_object().and_then(|error| {
+            let details: serde_json::Map<String, Value> = error
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "type" | "message"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            (!details.is_empty()).then(|| Box::new(Value::Object(details)))
+        });
+
+        Self::Api {
+            status,
+            error_type,
+            message,
+            param,
+            details,
+            body,
+        }
+    }
+}
+
+/// Extract the parameter path an error message starts with, if any.
+fn error_param(message: &str) -> Option<String> {
+    let (param, _) = message.split_once(": ")?;
+    let is_path = !param.is_empty()
+        && param
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '[' | ']'));
+
+    is_path.then(|| param.to_owned())
+}
+
+impl From<reqwest::Error> for AnthropicError {
+    #[inline(always)]
+    fn from(error: reqwest::Error) -> Self {
+        Self::Http(error)
+    }
+}
+
+impl From<std::io::Error> for AnthropicError {
+    #[inline(always)]
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for AnthropicError {
+    #[inline(always)]
+    fn from(error: serde_json::Error) -> Self {
+        Self::Deserialize(error)
+    }
+}
@@ -0,0 +1,222 @@
+use super::{
+    client::{AnthropicClient, RequestOptions},
+    error::{AnthropicError, Result},
+    page::{ListParams, Page},
+};
+use bytes::Bytes;
+use reqwest::{header::HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::RandomState,
+    future,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Beta feature of the files endpoints, sent in the `anthropic-beta` header.
+pub const FILES_BETA: &str = "files-api-2025-04-14";
+
+/// [`FilesApi`] struct to interact with the files endpoints of the API.
+///
+/// Uploaded files are referenced from messages by id, e.g. with a `document`
+/// block whose source is `{"type": "file", "file_id": "file_01..."}`, instead
+/// of being sent again with every request. The endpoints are in beta, so
+/// every request carries the [`FILES_BETA`] header.
+pub struct FilesApi<'a>(pub(crate) &'a AnthropicClient);
+
+/// Struct representing the metadata of an uploaded file.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileMetadata {
+    /// Unique object identifier, referenced by messages.
+    pub id: String,
+
+    /// Name of the file, as uploaded.
+    pub filename: String,
+
+    /// Media type of the file, e.g. `application/pdf`.
+    pub mime_type: String,
+
+    /// Size of the file, in bytes.
+    pub size_bytes: u64,
+
+    /// When the file was uploaded, as an RFC 3339 datetime.
+    pub created_at: String,
+
+    /// Whether the content of the file can be downloaded.
+    ///
+    /// Only files created by tools, e.g. code execution, can be; uploaded
+    /// files cannot.
+    #[serde(default)]
+    pub downloadable: bool,
+}
+
+/// Struct representing the confirmation of a file deletion.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DeletedFile {
+    /// Id of the deleted file.
+    pub id: String,
+}
+
+impl FilesApi<'_> {
+    /// Upload a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - The name of the file, e.g. `report.pdf`.
+    /// * `bytes` - The content of the file.
+    /// * `media_type` - The media type of the file, e.g. `application/pdf`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`FileMetadata`] of the uploaded file on
+    /// success, or an [`AnthropicError`] on failure. A media type that is not
+    /// of the form `type/subtype`, or cannot appear in a header, is rejected
+    /// with [`AnthropicError::Validation`] before anything is sent.
+    pub async fn upload(
+        &self,
+        filename: &str,
+        bytes: impl AsRef<[u8]>,
+        media_type: &str,
+    ) -> Result<FileMetadata> {
+        check_media_type(media_type)?;
+        let boundary = boundary();
+        let body = multipart_body(&boundary, filename, bytes.as_ref(), media_type);
+        let options = RequestOptions {
+            content_type: Some(format!("multipart/form-data; boundary={boundary}")),
+            ..options()
+        };
+
+        let (file, _) = self
+            .0
+            .send(Method::POST, "/files", Some(body), &options)
+            .await?;
+        Ok(file)
+    }
+
+    /// List the uploaded files, most recent first.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The [`ListParams`] selecting the page.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`Page`] of [`FileMetadata`] on success, or
+    /// an [`AnthropicError`] on failure.
+    pub async fn list(&self, params: &ListParams) -> Result<Page<FileMetadata>> {
+        let (page, _) = self
+            .0
+            .send(Method::GET, &params.to_path("/files"), None, &options())
+            .await?;
+        Ok(page)
+    }
+
+    /// Retrieve the metadata of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_id` - The id of the file.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`FileMetadata`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn retrieve(&self, file_id: &str) -> Result<FileMetadata> {
+        let url = format!("/files/{file_id}");
+        let (file, _) = self.0.send(Method::GET, &url, None, &options()).await?;
+        Ok(file)
+    }
+
+    /// Delete a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_id` - The id of the file.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`DeletedFile`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn delete(&self, file_id: &str) -> Result<DeletedFile> {
+        let url = format!("/files/{file_id}");
+        let (deleted, _) = self.0.send(Method::DELETE, &url, None, &options()).await?;
+        Ok(deleted)
+    }
+
+    /// Download the content of a file.
+    ///
+    /// Only files marked [`FileMetadata::downloadable`] can be downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_id` - The id of the file.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the content of the file on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn download(&self, file_id: &str) -> Result<Bytes> {
+        let (response, mut guard) = self
+            .0
+            .get_stream(&format!("/files/{file_id}/content"), &options())
+            .await?;
+
+        tokio::select! {
+            bytes = response.bytes() => Ok(bytes?),
+            () = future::poll_fn(|cx| guard.poll_cancelled(cx)) => Err(AnthropicError::Cancelled),
+        }
+    }
+}
+
+/// Get the options of a request to the files endpoints.
+#[inline(always)]
+fn options() -> RequestOptions {
+    RequestOptions {
+        beta: Some(FILES_BETA),
+        ..Default::default()
+    }
+}
+
+/// Check that a media type can be written to the `Content-Type` header of a
+/// part without ending it early.
+fn check_media_type(media_type: &str) -> Result<()> {
+    let well_formed = HeaderValue::from_str(media_type).is_ok()
+        && media_type
+            .split_once('/')
+            .is_some_and(|(kind, subtype)| !kind.is_empty() && !subtype.is_empty());
+
+    if well_formed {
+        Ok(())
+    } else {
+        Err(AnthropicError::Validation(format!(
+            "invalid media type {media_type:?}"
+        )))
+    }
+}
+
+/// Generate a multipart boundary.
+///
+/// The boundary is random so that it is vanishingly unlikely to appear in
+/// the content of the file.
+fn boundary() -> String {
+    let random = RandomState::new().build_hasher().finish();
+    format!("rusty-anthropic-{random:016x}")
+}
+
+/// Build a `multipart/form-data` body holding a single file.
+fn multipart_body(boundary: &str, filename: &str, bytes: &[u8], media_type: &str) -> Vec<u8> {
+    // Quotes and line breaks would end the header value early.
+    let filename = filename
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; \
+         filename=\"{filename}\"\r\nContent-Type: {media_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    body
+}
@@ -0,0 +1,187 @@
+use super::{
+    content::ToolUse,
+    error::Result,
+    messages::{MessageResponse, StopReason, Usage},
+};
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::value::RawValue;
+use std::{borrow::Cow, collections::HashMap};
+
+/// [`LazyMessageResponse`] struct holding a message response parsed on demand.
+///
+/// [`MessageResponse`] parses the whole body up front, which for responses
+/// embedding large data, e.g. a big code execution result, builds every value
+/// of it only to read the text. This keeps the body as received instead, and
+/// each accessor only builds what it returns: other fields and blocks are
+/// scanned over without being allocated.
+///
+/// The tradeoff is that every accessor scans the body again, so reading most
+/// of the response this way costs more than parsing it once; use
+/// [`LazyMessageResponse::parse`] for that. The strict deserialization of the
+/// client does not apply to the accessors.
+#[derive(Clone, Debug)]
+pub struct LazyMessageResponse {
+    /// The raw JSON body of the response.
+    body: Bytes,
+}
+
+/// Struct holding the content blocks of a response as unparsed JSON.
+#[derive(Deserialize)]
+struct RawContent<'a> {
+    /// The content blocks, in order.
+    #[serde(borrow, default)]
+    content: Vec<&'a RawValue>,
+}
+
+/// Struct reading only the type and text of a content block.
+#[derive(Deserialize)]
+struct BlockText<'a> {
+    /// Type of the block.
+    #[serde(rename = "type", borrow)]
+    kind: Cow<'a, str>,
+
+    /// Text of the block, for text blocks.
+    #[serde(borrow, default)]
+    text: Option<Cow<'a, str>>,
+}
+
+impl LazyMessageResponse {
+    /// Create a new instance of [`LazyMessageResponse`] from a response body.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The raw JSON body of a messages response.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`LazyMessageResponse`]. The body is only checked
+    /// when an accessor is called.
+    #[inline(always)]
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        Self { body: body.into() }
+    }
+
+    /// Get the raw JSON body of the response.
+    #[inline(always)]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Parse a single top-level field of the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the field, e.g. `id` or `model`.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the parsed field, or `None` if the response
+    /// has no such field, on success, or an [`AnthropicError`] if the body or
+    /// the field cannot be parsed.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub fn field<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let fields: HashMap<&str, &RawValue> = serde_json::from_slice(&self.body)?;
+        let field = fields.get(key).map(|raw| serde_json::from_str(raw.get()));
+
+        Ok(field.transpose()?)
+    }
+
+    /// Parse the reason the model stopped generating.
+    #[inline(always)]
+    pub fn stop_reason(&self) -> Result<Option<StopReason>> {
+        Ok(self.field("stop_reason")?.flatten())
+    }
+
+    /// Parse the token usage of the request.
+    #[inline(always)]
+    pub fn usage(&self) -> Result<Usage> {
+        Ok(self.field("usage")?.unwrap_or_default())
+    }
+
+    /// Count the content blocks of the response.
+    #[inline(always)]
+    pub fn block_count(&self) -> Result<usize> {
+        Ok(self.raw_content()?.content.len())
+    }
+
+    /// Parse a single content block of the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the block in the content.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the block parsed as `T`, e.g. a
+    /// [`serde_json::Value`] or a [`ContentBlock`], or `None` if there is no
+    /// block at `index`, on success, or an [`AnthropicError`] on failure.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    /// [`ContentBlock`]: super::content::ContentBlock
+    pub fn block<T: DeserializeOwned>(&self, index: usize) -> Result<Option<T>> {
+        let content = self.raw_content()?.content;
+        let block = content
+            .get(index)
+            .map(|raw| serde_json::from_str(raw.get()));
+
+        Ok(block.transpose()?)
+    }
+
+    /// Concatenate the text blocks of the response.
+    ///
+    /// Other blocks, however large, are skipped without being parsed.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the text of the response on success, or an
+    /// [`AnthropicError`] if the body cannot be parsed.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub fn text(&self) -> Result<String> {
+        let mut text = String::new();
+        for raw in self.raw_content()?.content {
+            let block: BlockText = serde_json::from_str(raw.get())?;
+            if let ("text", Some(block_text)) = (block.kind.as_ref(), block.text) {
+                text.push_str(&block_text);
+            }
+        }
+
+        Ok(text)
+    }
+
+    /// Parse the tool calls made by the model in the response.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the `tool_use` blocks of the content, in order,
+    /// on success, or an [`AnthropicError`] if the body cannot be parsed.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub fn tool_uses(&self) -> Result<Vec<ToolUse>> {
+        let mut tool_uses = Vec::new();
+        for raw in self.raw_content()?.content {
+            let block: BlockText = serde_json::from_str(raw.get())?;
+            if block.kind == "tool_use" {
+                tool_uses.push(serde_json::from_str(raw.get())?);
+            }
+        }
+
+        Ok(tool_uses)
+    }
+
+    /// Parse the whole response, as [`MessagesApi::create`] does.
+    ///
+    /// [`MessagesApi::create`]: super::messages::MessagesApi::create
+    #[inline(always)]
+    pub fn parse(&self) -> Result<MessageResponse> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// Split the content of the response into unparsed blocks.
+    #[inline(always)]
+    fn raw_content(&self) -> Result<RawContent<'_>> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
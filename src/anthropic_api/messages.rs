@@ -1,13 +1,61 @@
-use super::client::AnthropicClient;
+use super::{
+    cache,
+    client::AnthropicClient,
+    content::{self, ContentBlock, ImageOrderWarning, Role, ToolUse},
+    error::{AnthropicError, Result},
+    lazy::LazyMessageResponse,
+    meta::ResponseMeta,
+    model::Model,
+    streaming::MessageStream,
+    tokens::{estimate_message_tokens, estimate_tokens, estimate_value_tokens},
+};
 use crate::setters;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
+use std::{
+    iter::Sum,
+    ops::{Add, AddAssign},
+    time::{Duration, Instant},
+};
 
 /// [`MessagesApi`] struct to interact with the messages endpoint of the API.
-pub struct MessagesApi<'a, 'b, 'c>(pub(crate) &'a AnthropicClient<'b, 'c>);
+pub struct MessagesApi<'a>(pub(crate) &'a AnthropicClient);
+
+/// Enum representing the reason the model stopped generating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model reached a natural stopping point.
+    EndTurn,
+
+    /// The requested `max_tokens` limit was reached.
+    MaxTokens,
+
+    /// One of the provided stop sequences was generated.
+    StopSequence,
+
+    /// The model invoked one or more tools.
+    ToolUse,
+
+    /// A long-running server tool paused the turn.
+    ///
+    /// The response is incomplete: send it back as-is as the last assistant
+    /// message and the model will continue the same turn.
+    PauseTurn,
+
+    /// Input and output filled the context window of the model before it finished.
+    ///
+    /// Unlike [`StopReason::MaxTokens`], raising `max_tokens` does not help:
+    /// trim the input, e.g. older turns of the conversation, and retry.
+    ModelContextWindowExceeded,
+
+    /// Any stop reason not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
 
 /// Struct representing a request to send messages.
-#[derive(Default, Serialize)]
+#[derive(Clone, Default, Serialize)]
 pub struct MessageRequest {
     /// Model name to be used for generating messages.
     model: String,
@@ -23,6 +71,14 @@ pub struct MessageRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f64>,
 
+    /// Optional nucleus sampling probability mass.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+
+    /// Optional number of most likely tokens to sample from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u64>,
+
     /// Optional sequences to stop generation.
     #[serde(skip_serializing_if = "Option::is_none")]
     stop_sequences: Option<Vec<String>>,
@@ -30,6 +86,346 @@ pub struct MessageRequest {
     /// Optional flag for whether to stream back partial progress.
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+
+    /// Optional system prompt, either a string or an array of content blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<Value>,
+
+    /// Optional id of a code execution container to reuse.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    container: Option<String>,
+
+    /// Optional definitions of the tools the model may call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Value>>,
+
+    /// Optional constraint on how the model uses the tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+
+    /// Optional metadata about the request, e.g. the end user it is made for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<Value>,
+
+    /// Optional extended thinking configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ThinkingConfig>,
+
+    /// Optional capacity the request may be served from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_tier: Option<ServiceTier>,
+}
+
+/// Enum representing the capacity a request may be served from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceTier {
+    /// Priority capacity when the organization has some, standard otherwise.
+    Auto,
+
+    /// Standard capacity only, even when priority capacity is available.
+    StandardOnly,
+}
+
+/// Enum representing the capacity a request was actually served from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsedServiceTier {
+    /// Standard capacity.
+    Standard,
+
+    /// Priority capacity, billed at priority rates.
+    Priority,
+
+    /// Batch capacity, for requests of a message batch.
+    Batch,
+
+    /// Any tier not yet known to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Smallest thinking budget the API accepts, in tokens.
+pub const MIN_THINKING_BUDGET_TOKENS: u64 = 1_024;
+
+/// Enum representing the extended thinking configuration of a request.
+///
+/// With thinking enabled, the response starts with
+/// [`ContentBlock::Thinking`] blocks holding the reasoning of the model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThinkingConfig {
+    /// The model reasons before answering.
+    Enabled {
+        /// Maximum number of tokens the model may spend reasoning, at least
+        /// [`MIN_THINKING_BUDGET_TOKENS`] and below `max_tokens`, which it
+        /// counts towards.
+        budget_tokens: u64,
+    },
+
+    /// The model answers right away.
+    Disabled,
+}
+
+impl ThinkingConfig {
+    /// Create a new instance of [`ThinkingConfig`] enabling thinking.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget_tokens` - The maximum number of tokens to spend reasoning.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ThinkingConfig::Enabled`].
+    #[inline(always)]
+    pub const fn enabled(budget_tokens: u64) -> Self {
+        Self::Enabled { budget_tokens }
+    }
+}
+
+/// Struct representing the metadata of a message request.
+///
+/// Convert it into a [`Value`] to pass it to [`MessageRequest::metadata`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Opaque id of the end user the request is made for, helping Anthropic
+    /// detect abuse. Use a hash or uuid, never a name, email, or phone number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+impl Metadata {
+    /// Create a new instance of [`Metadata`] for an end user.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - The opaque id of the end user.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Metadata`].
+    #[inline(always)]
+    pub fn user(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: Some(user_id.into()),
+        }
+    }
+}
+
+impl From<Metadata> for Value {
+    #[inline(always)]
+    fn from(metadata: Metadata) -> Self {
+        serde_json::to_value(metadata).unwrap_or_default()
+    }
+}
+
+/// Struct representing the body of a token counting request.
+///
+/// Only the inputs of a message request are sent, the endpoint rejecting
+/// generation settings such as `max_tokens`.
+#[derive(Serialize)]
+struct CountTokensRequest<'a> {
+    /// Model name the tokens are counted for.
+    model: &'a str,
+
+    /// History of messages in the conversation.
+    messages: &'a [Value],
+
+    /// Optional system prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a Value>,
+
+    /// Optional definitions of the tools the model may call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [Value]>,
+
+    /// Optional constraint on how the model uses the tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'a Value>,
+
+    /// Optional extended thinking configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<&'a ThinkingConfig>,
+}
+
+/// Struct representing the response of the token counting endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TokenCount {
+    /// Number of input tokens the request would use.
+    pub input_tokens: u64,
+}
+
+/// Struct representing the sampling parameters the API uses for a request.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct SamplingParams {
+    /// Sampling temperature, [`DEFAULT_TEMPERATURE`] when not set.
+    pub temperature: f64,
+
+    /// Nucleus sampling probability mass, `None` when disabled.
+    pub top_p: Option<f64>,
+
+    /// Number of most likely tokens to sample from, `None` when disabled.
+    pub top_k: Option<u64>,
+}
+
+/// Temperature used by the API when a request does not set one.
+pub const DEFAULT_TEMPERATURE: f64 = 1.0;
+
+/// Struct representing whether a request fits in the context window of a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextFit {
+    /// Estimated number of input tokens of the request, system prompt and
+    /// tool definitions included.
+    pub estimated_input_tokens: u64,
+
+    /// Maximum number of output tokens requested.
+    pub max_output_tokens: u64,
+
+    /// Context window of the model.
+    pub context_window: u64,
+
+    /// Tokens left in the context window once input and output are reserved,
+    /// or zero if the request does not fit.
+    pub headroom: u64,
+
+    /// Whether input and requested output fit in the context window.
+    pub fits: bool,
+}
+
+/// Struct representing a response from the messages endpoint.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MessageResponse {
+    /// Unique object identifier.
+    pub id: String,
+
+    /// Object type. Always `message`.
+    #[serde(rename = "type", default = "default_message_type")]
+    pub message_type: String,
+
+    /// Model that handled the request.
+    pub model: String,
+
+    /// Role of the generated message. Always assistant.
+    pub role: Role,
+
+    /// Content blocks generated by the model.
+    ///
+    /// Blocks of a type this crate does not know are kept as
+    /// [`ContentBlock::Unknown`]; use [`MessagesApi::create_raw`] to read the
+    /// response untyped.
+    pub content: Vec<ContentBlock>,
+
+    /// Reason the model stopped generating.
+    pub stop_reason: Option<StopReason>,
+
+    /// Stop sequence that was generated, if any.
+    pub stop_sequence: Option<String>,
+
+    /// Token usage of the request.
+    pub usage: Usage,
+
+    /// Code execution container used by the request, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<Container>,
+
+    /// Raw response, only set when the response is degraded.
+    ///
+    /// See [`MessageResponse::salvage`].
+    #[serde(skip)]
+    pub raw: Option<Value>,
+}
+
+/// Struct representing the token usage of a request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Usage {
+    /// Number of input tokens that were processed.
+    pub input_tokens: u64,
+
+    /// Number of output tokens that were generated.
+    pub output_tokens: u64,
+
+    /// Number of input tokens written to the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+
+    /// Number of input tokens read from the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+
+    /// Capacity the request was served from, if reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<UsedServiceTier>,
+}
+
+impl Usage {
+    /// Get the total number of tokens of the request.
+    ///
+    /// `input_tokens` only counts the input processed without the cache, so
+    /// tokens written to and read from the cache are added to input and
+    /// output tokens.
+    #[inline(always)]
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens.unwrap_or_default()
+            + self.cache_read_input_tokens.unwrap_or_default()
+    }
+}
+
+impl AddAssign for Usage {
+    /// Add the usage of another request, e.g. to total a multi-step turn.
+    ///
+    /// Cache counts missing on one side count as zero, and stay `None` only
+    /// when missing on both. The service tier of `self` is kept, if any.
+    fn add_assign(&mut self, other: Self) {
+        fn add(left: Option<u64>, right: Option<u64>) -> Option<u64> {
+            match (left, right) {
+                (None, None) => None,
+                (left, right) => Some(left.unwrap_or_default() + right.unwrap_or_default()),
+            }
+        }
+
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens = add(
+            self.cache_creation_input_tokens,
+            other.cache_creation_input_tokens,
+        );
+        self.cache_read_input_tokens =
+            add(self.cache_read_input_tokens, other.cache_read_input_tokens);
+        self.service_tier = self.service_tier.or(other.service_tier);
+    }
+}
+
+impl Add for Usage {
+    type Output = Self;
+
+    #[inline(always)]
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl Sum for Usage {
+    #[inline(always)]
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Struct representing the code execution container of a response.
+///
+/// Pass [`Container::id`] to [`MessageRequest::container`] in a follow-up
+/// request to keep using the same container, and with it any files created
+/// in it, until [`Container::expires_at`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Container {
+    /// Identifier of the container.
+    pub id: String,
+
+    /// RFC 3339 timestamp after which the container is no longer available.
+    pub expires_at: String,
 }
 
 impl MessageRequest {
@@ -75,6 +471,28 @@ impl MessageRequest {
         /// The updated instance of [`MessageRequest`].
         temperature: f64,
 
+        /// Set the nucleus sampling probability mass for the response.
+        ///
+        /// # Arguments
+        ///
+        /// * `top_p` - Cumulative probability of the tokens to sample from, between 0 and 1.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        top_p: f64,
+
+        /// Set the number of most likely tokens to sample from for the response.
+        ///
+        /// # Arguments
+        ///
+        /// * `top_k` - Number of tokens to sample from.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        top_k: u64,
+
         /// Set the stop sequences for the response.
         ///
         /// # Arguments
@@ -88,6 +506,9 @@ impl MessageRequest {
 
         /// Set the stream flag for the response.
         ///
+        /// Only [`MessagesApi::create_stream`] can read a streamed response, and
+        /// it sets the flag itself; the other methods reject requests setting it.
+        ///
         /// # Arguments
         ///
         /// * `stream` - A boolean flag indicating whether to stream partial results.
@@ -96,10 +517,460 @@ impl MessageRequest {
         ///
         /// The updated instance of [`MessageRequest`].
         stream: bool,
+
+        /// Set the system prompt for the request.
+        ///
+        /// The prompt is either a string, e.g. `"Be brief.".into()`, or an
+        /// array of text blocks; see [`MessageRequest::system_blocks`].
+        ///
+        /// # Arguments
+        ///
+        /// * `system` - The system prompt giving context and instructions to the model.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        system: Value,
+
+        /// Set the code execution container to reuse for the request.
+        ///
+        /// # Arguments
+        ///
+        /// * `container` - The id of a container returned by a previous response.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        container: impl Into<String>,
+
+        /// Set the tools the model may call.
+        ///
+        /// # Arguments
+        ///
+        /// * `tools` - The tool definitions, each with a `name`, `description`, and
+        ///   `input_schema`, e.g. built with [`Tool`](super::tools::Tool).
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        tools: Vec<Value>,
+
+        /// Set how the model should use the tools.
+        ///
+        /// # Arguments
+        ///
+        /// * `tool_choice` - The constraint, e.g. `{"type": "auto"}`, `{"type": "any"}`
+        ///   to force a tool call, or `{"type": "tool", "name": "get_weather"}` to
+        ///   force a call to a given tool.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        tool_choice: Value,
+
+        /// Set the metadata of the request.
+        ///
+        /// # Arguments
+        ///
+        /// * `metadata` - The metadata object, e.g. built with [`Metadata`]:
+        ///   `Metadata::user("5f3a…").into()`.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        metadata: Value,
+
+        /// Set the extended thinking configuration of the request.
+        ///
+        /// # Arguments
+        ///
+        /// * `thinking` - The configuration, e.g. `ThinkingConfig::enabled(4_096)`.
+        ///   The budget must stay below `max_tokens`, which is checked before
+        ///   the request is sent.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        thinking: ThinkingConfig,
+
+        /// Set the capacity the request may be served from.
+        ///
+        /// The tier actually used is returned in [`Usage::service_tier`].
+        ///
+        /// # Arguments
+        ///
+        /// * `service_tier` - [`ServiceTier::Auto`] to use priority capacity when
+        ///   available, or [`ServiceTier::StandardOnly`].
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MessageRequest`].
+        service_tier: ServiceTier,
+    }
+
+    /// Add a sequence at which to stop the response, keeping those already set.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_sequence` - The sequence, which must contain non-whitespace.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageRequest`].
+    #[inline(always)]
+    pub fn stop_sequence(mut self, stop_sequence: impl Into<String>) -> Self {
+        self.stop_sequences
+            .get_or_insert_with(Vec::new)
+            .push(stop_sequence.into());
+        self
+    }
+
+    /// Replace the messages of the request, keeping every other setting.
+    ///
+    /// Clone the request first to keep the original, e.g. to re-ask with
+    /// trimmed context or a rephrased prompt in a loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The new history of messages in the conversation.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageRequest`].
+    #[inline(always)]
+    pub fn with_messages(mut self, messages: Vec<Value>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Set the system prompt for the request as content blocks.
+    ///
+    /// Unlike a plain string, blocks can carry metadata of their own, such as
+    /// `cache_control` to cache a long system prompt.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - The text blocks of the system prompt, in order.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageRequest`].
+    #[inline(always)]
+    pub fn system_blocks(self, blocks: Vec<Value>) -> Self {
+        self.system(Value::Array(blocks))
+    }
+
+    /// Set the system prompt for the request as a single cached text block.
+    ///
+    /// The block ends a cached prefix, so a long system prompt repeated across
+    /// requests is read from the cache instead of processed again. Check
+    /// [`Usage::cache_read_input_tokens`] to confirm caching worked.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system prompt, identical across requests.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageRequest`].
+    #[inline(always)]
+    pub fn system_cached(self, system: impl Into<String>) -> Self {
+        self.system_blocks(vec![cache::cache_control(ContentBlock::text(system))])
+    }
+}
+
+impl MessageResponse {
+    /// Build a degraded response from a raw response that failed to deserialize.
+    ///
+    /// Each field is parsed on its own, falling back to its default when it
+    /// cannot be parsed, and the full raw response is kept in
+    /// [`MessageResponse::raw`] so nothing is lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The raw JSON response.
+    ///
+    /// # Returns
+    ///
+    /// A degraded instance of [`MessageResponse`].
+    pub fn salvage(value: Value) -> Self {
+        fn field<T: DeserializeOwned + Default>(value: &Value, key: &str) -> T {
+            T::deserialize(&value[key]).unwrap_or_default()
+        }
+
+        Self {
+            id: field(&value, "id"),
+            message_type: default_message_type(),
+            model: field(&value, "model"),
+            role: Role::Assistant,
+            content: field(&value, "content"),
+            stop_reason: field(&value, "stop_reason"),
+            stop_sequence: field(&value, "stop_sequence"),
+            usage: field(&value, "usage"),
+            container: field(&value, "container"),
+            raw: Some(value),
+        }
+    }
+
+    /// Get the text of the response.
+    ///
+    /// # Returns
+    ///
+    /// The text of every `text` block of the content, concatenated in order.
+    /// Other blocks, such as tool calls and thinking, are skipped.
+    pub fn text(&self) -> String {
+        self.text_blocks().collect()
+    }
+
+    /// Get the text of the first `text` block of the response.
+    ///
+    /// # Returns
+    ///
+    /// The text of the first `text` block, or `None` if the content has none,
+    /// e.g. when the model only called tools.
+    #[inline(always)]
+    pub fn first_text(&self) -> Option<&str> {
+        self.text_blocks().next()
+    }
+
+    /// Get the text of the `text` blocks of the content, in order.
+    fn text_blocks(&self) -> impl Iterator<Item = &str> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Get the tool calls made by the model in the response.
+    ///
+    /// # Returns
+    ///
+    /// The `tool_use` blocks of the content, in order. Every call must be
+    /// answered with a tool result referencing its [`ToolUse::id`]. Check
+    /// [`MessageResponse::truncated_tool_use`] before executing them.
+    #[inline(always)]
+    pub fn tool_uses(&self) -> Vec<ToolUse> {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse(tool_use) => Some(tool_use.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the tool call whose input may have been cut off by `max_tokens`.
+    ///
+    /// When the response stops on [`StopReason::MaxTokens`] or
+    /// [`StopReason::ModelContextWindowExceeded`] and its last block is a tool
+    /// call, the model may not have finished writing the input: it can miss
+    /// arguments or hold partial values while still being valid JSON. Such a
+    /// call should not be executed; retry the request with a higher
+    /// `max_tokens` or a shorter input instead.
+    ///
+    /// # Returns
+    ///
+    /// The tool call that may be truncated, or `None` if every tool call of
+    /// the response is complete.
+    pub fn truncated_tool_use(&self) -> Option<ToolUse> {
+        if !matches!(
+            self.stop_reason,
+            Some(StopReason::MaxTokens | StopReason::ModelContextWindowExceeded)
+        ) {
+            return None;
+        }
+
+        match self.content.last()? {
+            ContentBlock::ToolUse(tool_use) => Some(tool_use.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the model stopped because the context window was full.
+    ///
+    /// The response is cut off; see [`StopReason::ModelContextWindowExceeded`].
+    #[inline(always)]
+    pub fn context_window_exceeded(&self) -> bool {
+        self.stop_reason == Some(StopReason::ModelContextWindowExceeded)
+    }
+
+    /// Whether the response is degraded, i.e. built by
+    /// [`MessageResponse::salvage`] after failing to deserialize.
+    #[inline(always)]
+    pub fn is_degraded(&self) -> bool {
+        self.raw.is_some()
+    }
+}
+
+impl MessageRequest {
+    /// Check locally whether the request fits in the context window of a model.
+    ///
+    /// The input size, system prompt and tool definitions included, is
+    /// estimated locally with the heuristic of [`estimate_message_tokens`], so
+    /// keep a margin when the headroom is small.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model whose context window to check against.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`ContextFit`] on success, or an
+    /// [`AnthropicError::Validation`] if `max_tokens` is not set or the context
+    /// window of the model is unknown.
+    pub fn fits_context(&self, model: &Model) -> Result<ContextFit> {
+        let max_output_tokens = self.max_tokens.ok_or_else(|| {
+            AnthropicError::Validation("max_tokens must be set to check the context window".into())
+        })?;
+        let context_window = model.context_window().ok_or_else(|| {
+            AnthropicError::Validation(format!("context window of model {model} is unknown"))
+        })?;
+
+        let estimated_input_tokens = self.estimate_input_tokens();
+        let required = estimated_input_tokens + max_output_tokens;
+
+        Ok(ContextFit {
+            estimated_input_tokens,
+            max_output_tokens,
+            context_window,
+            headroom: context_window.saturating_sub(required),
+            fits: required <= context_window,
+        })
+    }
+
+    /// Set `max_tokens` to the largest output the model of the request allows.
+    ///
+    /// The maximum output of the model is taken from its
+    /// [`capabilities`](Model::capabilities), and lowered if needed so that the
+    /// estimated input and the output still fit in the context window
+    /// together.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the updated instance of [`MessageRequest`] on
+    /// success, or an [`AnthropicError::Validation`] if the capabilities of the
+    /// model are unknown or the input alone fills the context window.
+    pub fn max_tokens_to_model_limit(self) -> Result<Self> {
+        let model = Model::from_id(&self.model);
+        let capabilities = model.capabilities().ok_or_else(|| {
+            AnthropicError::Validation(format!("output limit of model {model} is unknown"))
+        })?;
+
+        let estimated_input_tokens = self.estimate_input_tokens();
+        let available = capabilities
+            .context_window
+            .saturating_sub(estimated_input_tokens);
+        if available == 0 {
+            return Err(AnthropicError::Validation(format!(
+                "estimated input of {estimated_input_tokens} tokens leaves no room for output \
+                 in the {} token context window of model {model}",
+                capabilities.context_window
+            )));
+        }
+
+        Ok(self.max_tokens(capabilities.max_output_tokens.min(available)))
+    }
+
+    /// Estimate the input tokens of the messages, system prompt and tool
+    /// definitions of the request.
+    ///
+    /// Tool definitions are estimated from their JSON, as their names,
+    /// descriptions and schemas are all part of the input.
+    fn estimate_input_tokens(&self) -> u64 {
+        let system = self.system.as_ref().map_or(0, estimate_value_tokens);
+        let tools: u64 = self
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| estimate_tokens(&tool.to_string()))
+            .sum();
+
+        estimate_message_tokens(&self.messages) + system + tools
+    }
+
+    /// Get the sampling parameters the API uses for the request.
+    ///
+    /// The API does not clamp out-of-range values but rejects them, so they are
+    /// validated here: `temperature` and `top_p` must be between 0 and 1.
+    /// Unset values are reported as the API applies them, which makes the
+    /// result suitable for logging the exact settings of a run. Requests are
+    /// checked this way before being sent by [`MessagesApi`].
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the effective [`SamplingParams`] on success, or
+    /// an [`AnthropicError::Validation`] if a value is out of range.
+    pub fn sampling(&self) -> Result<SamplingParams> {
+        let check = |name: &str, value: Option<f64>| match value {
+            Some(value) if !(0.0..=1.0).contains(&value) => Err(AnthropicError::Validation(
+                format!("{name} must be between 0 and 1, got {value}"),
+            )),
+            _ => Ok(value),
+        };
+
+        Ok(SamplingParams {
+            temperature: check("temperature", self.temperature)?.unwrap_or(DEFAULT_TEMPERATURE),
+            top_p: check("top_p", self.top_p)?,
+            top_k: self.top_k,
+        })
+    }
+
+    /// Find the user messages placing text before the images it refers to.
+    ///
+    /// Images placed before the text about them give the best results with
+    /// vision prompts. This check is opt-in and never blocks a request, since
+    /// some prompts interleave text and images on purpose. A text block ending
+    /// with a colon right before an image, such as the `Image 1:` labels of
+    /// [`Message::user_with_labeled_images`], is a label of that image and is
+    /// not reported.
+    ///
+    /// # Returns
+    ///
+    /// An [`ImageOrderWarning`] per user message placing text before an image,
+    /// in order. Fix them with [`MessageRequest::images_first`].
+    ///
+    /// [`Message::user_with_labeled_images`]: super::content::Message::user_with_labeled_images
+    pub fn image_order_warnings(&self) -> Vec<ImageOrderWarning> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message["role"] == "user")
+            .filter_map(|(index, message)| {
+                let (text_block, image_block) =
+                    content::text_before_image(message["content"].as_array()?)?;
+                Some(ImageOrderWarning {
+                    message: index,
+                    text_block,
+                    image_block,
+                })
+            })
+            .collect()
+    }
+
+    /// Move the images of every user message before its text.
+    ///
+    /// Text and image blocks are swapped into images first, each group
+    /// keeping its order, and image labels reported as such by
+    /// [`MessageRequest::image_order_warnings`] move along with their image.
+    /// Other blocks, such as tool results, keep their place.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageRequest`].
+    pub fn images_first(mut self) -> Self {
+        for message in &mut self.messages {
+            if message["role"] != "user" {
+                continue;
+            }
+            if let Some(blocks) = message.get_mut("content").and_then(Value::as_array_mut) {
+                content::images_first(blocks);
+            }
+        }
+        self
     }
 }
 
-impl<'a> MessagesApi<'a, '_, '_> {
+impl MessagesApi<'_> {
     /// Send a message request using the provided parameters.
     ///
     /// # Arguments
@@ -108,10 +979,275 @@ impl<'a> MessagesApi<'a, '_, '_> {
     ///
     /// # Returns
     ///
-    /// A [`Result`] containing the JSON response as [`serde_json::Value`] on success,
-    /// or a [`serde_json::Error`] on failure.
-    pub async fn create(&self, request: MessageRequest) -> reqwest::Result<Value> {
+    /// A [`Result`] containing the [`MessageResponse`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn create(&self, request: MessageRequest) -> Result<MessageResponse> {
+        let request = self.prepare(request, false)?;
+
         // Send a POST request to the messages endpoint with the request body.
         self.0.post("/messages", &request).await
     }
+
+    /// Send a message request with a time limit of its own.
+    ///
+    /// The limit overrides the one of the client for this request only, e.g.
+    /// to give a long generation more time or cut a quick one short.
+    /// Retries of the request count against the same limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    /// * `timeout` - The longest time the request may take, retries and
+    ///   response included.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageResponse`] on success,
+    /// [`AnthropicError::Timeout`] if it did not complete in time, or another
+    /// [`AnthropicError`] on failure.
+    pub async fn create_with_timeout(
+        &self,
+        request: MessageRequest,
+        timeout: Duration,
+    ) -> Result<MessageResponse> {
+        let request = self.prepare(request, false)?;
+        self.0
+            .post_with_timeout("/messages", &request, timeout)
+            .await
+    }
+
+    /// Send a message request identified by an idempotency key.
+    ///
+    /// [`MessagesApi::create`] already sends the same generated key in every
+    /// retry of a request; set one explicitly to also deduplicate requests
+    /// resent by the caller, e.g. after a crash, by reusing the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    /// * `idempotency_key` - The key of the logical request, e.g. from
+    ///   [`retry::idempotency_key`](super::retry::idempotency_key).
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageResponse`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn create_with_idempotency_key(
+        &self,
+        request: MessageRequest,
+        idempotency_key: impl Into<String>,
+    ) -> Result<MessageResponse> {
+        let request = self.prepare(request, false)?;
+        self.0
+            .post_idempotent("/messages", &request, idempotency_key)
+            .await
+    }
+
+    /// Send a message request and return the raw JSON response.
+    ///
+    /// Use this to read fields [`MessageResponse`] does not model yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the JSON response as [`serde_json::Value`] on success,
+    /// or an [`AnthropicError`] on failure.
+    pub async fn create_raw(&self, request: MessageRequest) -> Result<Value> {
+        let request = self.prepare(request, false)?;
+        self.0.post("/messages", &request).await
+    }
+
+    /// Send a message request and keep the response unparsed until read.
+    ///
+    /// Meant for responses embedding large data, e.g. big tool or code
+    /// execution results, when only part of them is needed; see
+    /// [`LazyMessageResponse`] for the tradeoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`LazyMessageResponse`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn create_lazy(&self, request: MessageRequest) -> Result<LazyMessageResponse> {
+        let request = self.prepare(request, false)?;
+        let body = self.0.post_bytes("/messages", &request).await?;
+
+        Ok(LazyMessageResponse::new(body))
+    }
+
+    /// Count the input tokens of a message request without sending it.
+    ///
+    /// Unlike [`estimate_message_tokens`], the count comes from the API, so it
+    /// is exact and includes the system prompt, tools and thinking
+    /// configuration. Only the model and inputs of the request are sent; other
+    /// settings are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The [`MessageRequest`] whose inputs to count.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`TokenCount`] on success, or an
+    /// [`AnthropicError`] on failure.
+    pub async fn count_tokens(&self, request: &MessageRequest) -> Result<TokenCount> {
+        let body = CountTokensRequest {
+            model: &request.model,
+            messages: &request.messages,
+            system: request.system.as_ref(),
+            tools: request.tools.as_deref(),
+            tool_choice: request.tool_choice.as_ref(),
+            thinking: request.thinking.as_ref(),
+        };
+
+        self.0.post("/messages/count_tokens", &body).await
+    }
+
+    /// Send a message request and keep the metadata of the response headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageResponse`] and its [`ResponseMeta`]
+    /// on success, or an [`AnthropicError`] on failure.
+    pub async fn create_with_meta(
+        &self,
+        request: MessageRequest,
+    ) -> Result<(MessageResponse, ResponseMeta)> {
+        let request = self.prepare(request, false)?;
+        self.0.post_with_meta("/messages", &request).await
+    }
+
+    /// Send a message request, salvaging the response if it fails to deserialize.
+    ///
+    /// Instead of failing when the response does not match [`MessageResponse`],
+    /// for instance in strict mode when the API added a field, a degraded
+    /// response is returned; check [`MessageResponse::is_degraded`] and read
+    /// the fields that could not be parsed from [`MessageResponse::raw`].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the possibly degraded [`MessageResponse`] on
+    /// success, or an [`AnthropicError`] if the request itself failed.
+    pub async fn create_salvaged(&self, request: MessageRequest) -> Result<MessageResponse> {
+        let request = self.prepare(request, false)?;
+        let value: Value = self.0.post("/messages", &request).await?;
+
+        Ok(self
+            .0
+            .deserialize_value(value.clone())
+            .unwrap_or_else(|_| MessageResponse::salvage(value)))
+    }
+
+    /// Send a message request and stream the response as it is generated.
+    ///
+    /// The `stream` flag of the request is set automatically. If the client
+    /// limits concurrent streams, this waits for or fails on a free slot first.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A [`MessageRequest`] containing the parameters for the messages request.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`MessageStream`] of events on success,
+    /// or an [`AnthropicError`] on failure.
+    pub async fn create_stream(&self, request: MessageRequest) -> Result<MessageStream> {
+        let mut request = self.prepare(request, true)?;
+        request.stream = Some(true);
+
+        let permit = self.0.acquire_stream_permit().await?;
+
+        // Send a POST request to the messages endpoint and stream the response body.
+        let start = Instant::now();
+        let (response, guard) = self.0.open_stream("/messages", &request).await?;
+        let stream = MessageStream::new(response, permit, guard);
+
+        Ok(if self.0.timing_enabled() {
+            stream.timed(start, start.elapsed())
+        } else {
+            stream
+        })
+    }
+
+    /// Apply the defaults of the client to a request and validate it.
+    pub(crate) fn prepare(
+        &self,
+        mut request: MessageRequest,
+        stream: bool,
+    ) -> Result<MessageRequest> {
+        request.sampling()?;
+        check_stop_sequences(request.stop_sequences.as_deref())?;
+
+        // A streamed body cannot be parsed as a single JSON response.
+        if !stream && request.stream == Some(true) {
+            return Err(AnthropicError::Validation(
+                "the request sets stream to true; use MessagesApi::create_stream to \
+                 receive the response as a stream of events"
+                    .to_owned(),
+            ));
+        }
+
+        if request.max_tokens.is_none() {
+            request.max_tokens = self.0.max_tokens_default();
+        }
+        if request.max_tokens.is_none() {
+            return Err(AnthropicError::Validation(
+                "max_tokens is required; set it on the request or a default on the client \
+                 with AnthropicClient::default_max_tokens"
+                    .to_owned(),
+            ));
+        }
+
+        if let (Some(ThinkingConfig::Enabled { budget_tokens }), Some(max_tokens)) =
+            (request.thinking, request.max_tokens)
+        {
+            if budget_tokens < MIN_THINKING_BUDGET_TOKENS {
+                return Err(AnthropicError::Validation(format!(
+                    "thinking budget_tokens must be at least {MIN_THINKING_BUDGET_TOKENS}, \
+                     got {budget_tokens}"
+                )));
+            }
+            if budget_tokens >= max_tokens {
+                return Err(AnthropicError::Validation(format!(
+                    "thinking budget_tokens ({budget_tokens}) must be less than max_tokens \
+                     ({max_tokens})"
+                )));
+            }
+        }
+
+        Ok(request)
+    }
+}
+
+/// Reject the stop sequences the API would reject, i.e. blank ones.
+pub(crate) fn check_stop_sequences(stop_sequences: Option<&[String]>) -> Result<()> {
+    match stop_sequences
+        .into_iter()
+        .flatten()
+        .position(|sequence| sequence.trim().is_empty())
+    {
+        Some(index) => Err(AnthropicError::Validation(format!(
+            "stop_sequences.{index} must contain non-whitespace"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Default of [`MessageResponse::message_type`] for responses lacking it.
+fn default_message_type() -> String {
+    "message".to_owned()
 }
@@ -0,0 +1,136 @@
+use super::retry;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// Struct representing the metadata carried by the headers of a response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// Unique id of the request, from the `request-id` header.
+    ///
+    /// Include it when reporting an issue to Anthropic.
+    pub request_id: Option<String>,
+
+    /// Id of the organization the API key belongs to, from the
+    /// `anthropic-organization-id` header.
+    ///
+    /// Lets integrations spanning several organizations confirm which one
+    /// served a request.
+    pub organization_id: Option<String>,
+
+    /// Rate limits of the organization, from the `anthropic-ratelimit-*` headers.
+    pub rate_limit: RateLimitInfo,
+
+    /// Timing of the round trip, only measured when
+    /// [`AnthropicClient::debug_timing`] is enabled.
+    ///
+    /// [`AnthropicClient::debug_timing`]: super::client::AnthropicClient::debug_timing
+    pub timing: Option<Timing>,
+}
+
+/// Struct representing the timing breakdown of a request round trip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    /// Time from sending the request to receiving the response headers.
+    pub time_to_first_byte: Duration,
+
+    /// Time from sending the request to reading the whole response body.
+    pub total: Duration,
+}
+
+impl ResponseMeta {
+    /// Read the metadata from the headers of a response.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+
+        Self {
+            request_id: header("request-id"),
+            organization_id: header("anthropic-organization-id"),
+            rate_limit: RateLimitInfo::from_headers(headers),
+            timing: None,
+        }
+    }
+}
+
+/// Struct representing the state of a single rate limit of the organization.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Maximum allowed within the current period.
+    pub limit: Option<u64>,
+
+    /// Remaining before being rate limited.
+    pub remaining: Option<u64>,
+
+    /// When the limit is fully replenished, as an RFC 3339 datetime.
+    pub reset: Option<String>,
+}
+
+/// Struct representing the rate limits reported by the headers of a response.
+///
+/// Every limit is `None` when the response does not report it, e.g. because
+/// it went through a proxy stripping the headers. Reading it after each
+/// request allows throttling before hitting a `429`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Limit on the number of requests, from `anthropic-ratelimit-requests-*`.
+    pub requests: Option<RateLimit>,
+
+    /// Limit on the number of tokens, from `anthropic-ratelimit-tokens-*`.
+    pub tokens: Option<RateLimit>,
+
+    /// Limit on the number of input tokens, from
+    /// `anthropic-ratelimit-input-tokens-*`.
+    pub input_tokens: Option<RateLimit>,
+
+    /// Limit on the number of output tokens, from
+    /// `anthropic-ratelimit-output-tokens-*`.
+    pub output_tokens: Option<RateLimit>,
+
+    /// Delay requested by the server before retrying, from `retry-after`.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Read the rate limits from the headers of a response.
+    ///
+    /// Also usable on the response of [`AnthropicClient::post_stream`].
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The headers of the response.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`RateLimitInfo`], with the limits the headers report.
+    ///
+    /// [`AnthropicClient::post_stream`]: super::client::AnthropicClient::post_stream
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let limit = |kind: &str| {
+            let header = |field: &str| {
+                headers
+                    .get(format!("anthropic-ratelimit-{kind}-{field}"))
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::trim)
+            };
+            let limit = RateLimit {
+                limit: header("limit").and_then(|value| value.parse().ok()),
+                remaining: header("remaining").and_then(|value| value.parse().ok()),
+                reset: header("reset").map(str::to_owned),
+            };
+
+            (limit != RateLimit::default()).then_some(limit)
+        };
+
+        Self {
+            requests: limit("requests"),
+            tokens: limit("tokens"),
+            input_tokens: limit("input-tokens"),
+            output_tokens: limit("output-tokens"),
+            retry_after: retry::retry_after(headers),
+        }
+    }
+}
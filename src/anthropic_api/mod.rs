@@ -1,4 +1,35 @@
+pub mod agent;
+pub mod audit;
+pub mod batches;
+pub mod cache;
+pub mod circuit;
 pub mod client;
+pub mod content;
+pub mod conversation;
+pub mod diff;
 pub mod embeddings;
+pub mod error;
+pub mod files;
+pub mod lazy;
 pub mod messages;
+pub mod meta;
+pub mod model;
+pub mod models;
+#[cfg(feature = "openai")]
+pub mod openai;
+pub mod page;
+pub mod pricing;
+pub mod retry;
+#[cfg(feature = "signing")]
+pub mod signing;
+mod sse;
+pub mod streaming;
+pub mod strict;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
 pub mod text_completions;
+pub mod tokens;
+pub mod tools;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod transport;
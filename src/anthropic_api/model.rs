@@ -0,0 +1,306 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{OnceLock, RwLock},
+};
+
+/// Enum representing a Claude model.
+///
+/// The known variants and their limits are a snapshot of the models documented
+/// when this crate was released; any other model id is kept as
+/// [`Model::Other`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Model {
+    /// Claude 3 Haiku.
+    Claude3Haiku,
+
+    /// Claude 3.5 Haiku.
+    Claude35Haiku,
+
+    /// Claude 3.5 Sonnet.
+    Claude35Sonnet,
+
+    /// Claude 3.7 Sonnet.
+    Claude37Sonnet,
+
+    /// Claude Sonnet 4.
+    ClaudeSonnet4,
+
+    /// Claude Opus 4.
+    ClaudeOpus4,
+
+    /// Claude Opus 4.1.
+    ClaudeOpus41,
+
+    /// Claude Sonnet 4.5.
+    ClaudeSonnet45,
+
+    /// Claude Haiku 4.5.
+    ClaudeHaiku45,
+
+    /// Claude Opus 4.5.
+    ClaudeOpus45,
+
+    /// Any model not known to this crate, by id.
+    Other(String),
+}
+
+/// Struct representing the capabilities and limits of a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    /// Whether the model accepts image content.
+    pub vision: bool,
+
+    /// Whether the model supports tool use.
+    pub tools: bool,
+
+    /// Whether the model supports extended thinking.
+    pub extended_thinking: bool,
+
+    /// Number of tokens input and output must fit in together.
+    pub context_window: u64,
+
+    /// Maximum number of output tokens per request.
+    pub max_output_tokens: u64,
+}
+
+impl ModelCapabilities {
+    /// Capabilities shared by most known models.
+    const BASE: Self = Self {
+        vision: true,
+        tools: true,
+        extended_thinking: true,
+        context_window: 200_000,
+        max_output_tokens: 64_000,
+    };
+
+    /// Capabilities of models released before extended thinking.
+    const fn without_thinking(max_output_tokens: u64) -> Self {
+        Self {
+            extended_thinking: false,
+            max_output_tokens,
+            ..Self::BASE
+        }
+    }
+
+    /// Capabilities with a different maximum number of output tokens.
+    const fn with_max_output(max_output_tokens: u64) -> Self {
+        Self {
+            max_output_tokens,
+            ..Self::BASE
+        }
+    }
+}
+
+/// Struct representing an entry of the known model table.
+struct KnownModel {
+    /// The model variant.
+    model: Model,
+
+    /// Dated id of the model.
+    id: &'static str,
+
+    /// Id prefix matching the aliases of the model.
+    alias: &'static str,
+
+    /// Documented capabilities of the model.
+    capabilities: ModelCapabilities,
+}
+
+/// Table of known models, as documented when this crate was released.
+const KNOWN_MODELS: [KnownModel; 10] = [
+    KnownModel {
+        model: Model::Claude3Haiku,
+        id: "claude-3-haiku-20240307",
+        alias: "claude-3-haiku",
+        capabilities: ModelCapabilities::without_thinking(4_096),
+    },
+    KnownModel {
+        model: Model::Claude35Haiku,
+        id: "claude-3-5-haiku-20241022",
+        alias: "claude-3-5-haiku",
+        capabilities: ModelCapabilities::without_thinking(8_192),
+    },
+    KnownModel {
+        model: Model::Claude35Sonnet,
+        id: "claude-3-5-sonnet-20241022",
+        alias: "claude-3-5-sonnet",
+        capabilities: ModelCapabilities::without_thinking(8_192),
+    },
+    KnownModel {
+        model: Model::Claude37Sonnet,
+        id: "claude-3-7-sonnet-20250219",
+        alias: "claude-3-7-sonnet",
+        capabilities: ModelCapabilities::BASE,
+    },
+    KnownModel {
+        model: Model::ClaudeSonnet4,
+        id: "claude-sonnet-4-20250514",
+        alias: "claude-sonnet-4-0",
+        capabilities: ModelCapabilities::BASE,
+    },
+    KnownModel {
+        model: Model::ClaudeOpus4,
+        id: "claude-opus-4-20250514",
+        alias: "claude-opus-4-0",
+        capabilities: ModelCapabilities::with_max_output(32_000),
+    },
+    KnownModel {
+        model: Model::ClaudeOpus41,
+        id: "claude-opus-4-1-20250805",
+        alias: "claude-opus-4-1",
+        capabilities: ModelCapabilities::with_max_output(32_000),
+    },
+    KnownModel {
+        model: Model::ClaudeSonnet45,
+        id: "claude-sonnet-4-5-20250929",
+        alias: "claude-sonnet-4-5",
+        capabilities: ModelCapabilities::BASE,
+    },
+    KnownModel {
+        model: Model::ClaudeHaiku45,
+        id: "claude-haiku-4-5-20251001",
+        alias: "claude-haiku-4-5",
+        capabilities: ModelCapabilities::BASE,
+    },
+    KnownModel {
+        model: Model::ClaudeOpus45,
+        id: "claude-opus-4-5-20251101",
+        alias: "claude-opus-4-5",
+        capabilities: ModelCapabilities::BASE,
+    },
+];
+
+/// Capabilities registered at runtime, by model id.
+fn registered() -> &'static RwLock<HashMap<String, ModelCapabilities>> {
+    static REGISTERED: OnceLock<RwLock<HashMap<String, ModelCapabilities>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+impl Model {
+    /// Parse a model from its id or alias.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The model id, e.g. `claude-sonnet-4-5` or `claude-sonnet-4-5-20250929`.
+    ///
+    /// # Returns
+    ///
+    /// The matching [`Model`], or [`Model::Other`] if the id is not known.
+    pub fn from_id(id: &str) -> Self {
+        KNOWN_MODELS
+            .iter()
+            .find(|known| id == known.id || id.starts_with(known.alias))
+            .map(|known| known.model.clone())
+            .unwrap_or_else(|| Self::Other(id.to_owned()))
+    }
+
+    /// Get the id of the model as sent to the API.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Other(id) => id,
+            model => KNOWN_MODELS
+                .iter()
+                .find(|known| known.model == *model)
+                .map(|known| known.id)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Register or override the capabilities of a model.
+    ///
+    /// This applies process-wide and takes precedence over the built-in table,
+    /// so models released after this crate, or corrections to the table, can
+    /// be supplied at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The model id, as returned by [`Model::id`].
+    /// * `capabilities` - The capabilities of the model.
+    pub fn register_capabilities(id: impl Into<String>, capabilities: ModelCapabilities) {
+        registered()
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(id.into(), capabilities);
+    }
+
+    /// Get the capabilities of the model.
+    ///
+    /// # Returns
+    ///
+    /// The registered capabilities of the model if any, otherwise its entry in
+    /// the built-in table, or `None` for a model not known to this crate.
+    pub fn capabilities(&self) -> Option<ModelCapabilities> {
+        let registered = registered()
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(self.id())
+            .copied();
+
+        registered.or_else(|| {
+            KNOWN_MODELS
+                .iter()
+                .find(|known| known.model == *self)
+                .map(|known| known.capabilities)
+        })
+    }
+
+    /// Whether the model accepts image content, `false` if unknown.
+    #[inline(always)]
+    pub fn supports_vision(&self) -> bool {
+        self.capabilities()
+            .is_some_and(|capabilities| capabilities.vision)
+    }
+
+    /// Whether the model supports tool use, `false` if unknown.
+    #[inline(always)]
+    pub fn supports_tools(&self) -> bool {
+        self.capabilities()
+            .is_some_and(|capabilities| capabilities.tools)
+    }
+
+    /// Whether the model supports extended thinking, `false` if unknown.
+    #[inline(always)]
+    pub fn supports_extended_thinking(&self) -> bool {
+        self.capabilities()
+            .is_some_and(|capabilities| capabilities.extended_thinking)
+    }
+
+    /// Get the context window of the model, in tokens.
+    ///
+    /// # Returns
+    ///
+    /// The number of tokens input and output must fit in together, or `None`
+    /// for a model not known to this crate.
+    #[inline(always)]
+    pub fn context_window(&self) -> Option<u64> {
+        self.capabilities()
+            .map(|capabilities| capabilities.context_window)
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl From<Model> for String {
+    #[inline(always)]
+    fn from(model: Model) -> Self {
+        model.id().to_owned()
+    }
+}
+
+impl Serialize for Model {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Model {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_id(&String::deserialize(deserializer)?))
+    }
+}
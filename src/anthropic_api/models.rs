@@ -0,0 +1,66 @@
+use super::{
+    client::AnthropicClient,
+    error::Result,
+    model::Model,
+    page::{ListParams, Page},
+};
+use serde::{Deserialize, Serialize};
+
+/// [`ModelsApi`] struct to interact with the models endpoints of the API.
+pub struct ModelsApi<'a>(pub(crate) &'a AnthropicClient);
+
+/// Struct representing a model available through the API.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ModelInfo {
+    /// Unique model identifier, as passed in requests.
+    pub id: String,
+
+    /// Human readable name of the model, e.g. `Claude Sonnet 4.5`.
+    pub display_name: String,
+
+    /// When the model was released, as an RFC 3339 datetime.
+    pub created_at: String,
+}
+
+impl ModelInfo {
+    /// Get the [`Model`] of this id, to look up its known capabilities.
+    #[inline(always)]
+    pub fn model(&self) -> Model {
+        Model::from_id(&self.id)
+    }
+}
+
+impl ModelsApi<'_> {
+    /// List the models available to the API key, most recent first.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The [`ListParams`] selecting the page.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`Page`] of [`ModelInfo`]s on success, or
+    /// an [`AnthropicError`] on failure.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub async fn list(&self, params: &ListParams) -> Result<Page<ModelInfo>> {
+        self.0.get(&params.to_path("/models")).await
+    }
+
+    /// Retrieve a model.
+    ///
+    /// # Arguments
+    ///
+    /// * `model_id` - The id of the model, or an alias such as
+    ///   `claude-sonnet-4-5`, resolved to the id it points to.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the [`ModelInfo`] on success, or an
+    /// [`AnthropicError`] on failure.
+    ///
+    /// [`AnthropicError`]: super::error::AnthropicError
+    pub async fn retrieve(&self, model_id: &str) -> Result<ModelInfo> {
+        self.0.get(&format!("/models/{model_id}")).await
+    }
+}
@@ -0,0 +1,96 @@
+use super::messages::{MessageResponse, StopReason};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+impl MessageResponse {
+    /// Convert the response to the shape of an OpenAI chat completion.
+    ///
+    /// Meant for layers abstracting over several providers, so they can
+    /// handle every response the same way. The fields correspond as follows:
+    ///
+    /// | Chat completion                     | Message response                                       |
+    /// |-------------------------------------|--------------------------------------------------------|
+    /// | `id`, `model`                       | `id`, `model`                                          |
+    /// | `created`                           | time of the conversion, responses carry no timestamp   |
+    /// | `choices[0].message.content`        | concatenated `text` blocks, `null` if there are none   |
+    /// | `choices[0].message.tool_calls`     | `tool_use` blocks, with the input as a JSON string     |
+    /// | `choices[0].finish_reason`          | `stop_reason`, see below                               |
+    /// | `usage.prompt_tokens`               | input tokens, including cache writes and reads         |
+    /// | `usage.prompt_tokens_details`       | cache reads as `cached_tokens`                         |
+    /// | `usage.completion_tokens`           | output tokens                                          |
+    ///
+    /// `end_turn` and `stop_sequence` become `stop`, `max_tokens` becomes
+    /// `length`, `tool_use` becomes `tool_calls`, and other stop reasons keep
+    /// their name, e.g. `pause_turn`.
+    ///
+    /// The conversion is lossy: thinking and redacted thinking blocks, server
+    /// tool blocks, citations, the generated stop sequence, the cache write
+    /// count, and the container are dropped, and text blocks interleaved with
+    /// tool calls are merged.
+    ///
+    /// # Returns
+    ///
+    /// A chat completion object, with a single choice.
+    pub fn to_openai(&self) -> Value {
+        let text = self.text();
+        let tool_calls: Vec<Value> = self
+            .tool_uses()
+            .into_iter()
+            .map(|tool_use| {
+                json!({
+                    "id": tool_use.id,
+                    "type": "function",
+                    "function": {
+                        "name": tool_use.name,
+                        "arguments": tool_use.input.to_string(),
+                    },
+                })
+            })
+            .collect();
+
+        let mut message = json!({
+            "role": "assistant",
+            "content": if text.is_empty() { Value::Null } else { Value::String(text) },
+        });
+        if !tool_calls.is_empty() {
+            message["tool_calls"] = Value::Array(tool_calls);
+        }
+
+        let usage = &self.usage;
+        let cache_read = usage.cache_read_input_tokens.unwrap_or(0);
+        let prompt_tokens =
+            usage.input_tokens + usage.cache_creation_input_tokens.unwrap_or(0) + cache_read;
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": created,
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "message": message,
+                "finish_reason": self.stop_reason.map(finish_reason),
+            }],
+            "usage": {
+                "prompt_tokens": prompt_tokens,
+                "completion_tokens": usage.output_tokens,
+                "total_tokens": prompt_tokens + usage.output_tokens,
+                "prompt_tokens_details": { "cached_tokens": cache_read },
+            },
+        })
+    }
+}
+
+/// Map a stop reason to the matching chat completion finish reason.
+fn finish_reason(stop_reason: StopReason) -> Value {
+    match stop_reason {
+        StopReason::EndTurn | StopReason::StopSequence => json!("stop"),
+        StopReason::MaxTokens | StopReason::ModelContextWindowExceeded => json!("length"),
+        StopReason::ToolUse => json!("tool_calls"),
+        other => serde_json::to_value(other).unwrap_or(Value::Null),
+    }
+}
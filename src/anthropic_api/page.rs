@@ -0,0 +1,116 @@
+use crate::setters;
+use serde::{Deserialize, Serialize};
+
+/// Struct representing a page of objects returned by a list endpoint.
+///
+/// Pass [`Page::last_id`] as [`ListParams::after_id`] to fetch the next page,
+/// or [`Page::first_id`] as [`ListParams::before_id`] for the previous one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Page<T> {
+    /// The objects of the page.
+    pub data: Vec<T>,
+
+    /// Whether there are more objects past this page.
+    pub has_more: bool,
+
+    /// Id of the first object of the page, if any.
+    #[serde(default)]
+    pub first_id: Option<String>,
+
+    /// Id of the last object of the page, if any.
+    #[serde(default)]
+    pub last_id: Option<String>,
+}
+
+/// [`ListParams`] struct selecting the page returned by a list endpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListParams {
+    /// Optional number of objects per page, from 1 to 1000, 20 by default.
+    limit: Option<u32>,
+
+    /// Optional id of the object the page ends right before.
+    before_id: Option<String>,
+
+    /// Optional id of the object the page starts right after.
+    after_id: Option<String>,
+}
+
+impl ListParams {
+    /// Create a new instance of [`ListParams`].
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ListParams`] selecting the first page.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    setters! {
+        /// Set the number of objects per page.
+        ///
+        /// # Arguments
+        ///
+        /// * `limit` - The number of objects, from 1 to 1000.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`ListParams`].
+        limit: u32,
+
+        /// Set the object the page ends right before.
+        ///
+        /// # Arguments
+        ///
+        /// * `before_id` - The id of the object, e.g. [`Page::first_id`].
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`ListParams`].
+        before_id: impl Into<String>,
+
+        /// Set the object the page starts right after.
+        ///
+        /// # Arguments
+        ///
+        /// * `after_id` - The id of the object, e.g. [`Page::last_id`].
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`ListParams`].
+        after_id: impl Into<String>,
+    }
+
+    /// Append the parameters set as the query string of `path`.
+    pub(crate) fn to_path(&self, path: &str) -> String {
+        let params = [
+            ("limit", self.limit.map(|limit| limit.to_string())),
+            ("before_id", self.before_id.clone()),
+            ("after_id", self.after_id.clone()),
+        ];
+        let query = params
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{name}={}", encode(&value?))))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        if query.is_empty() {
+            path.to_owned()
+        } else {
+            format!("{path}?{query}")
+        }
+    }
+}
+
+/// Percent-encode a query string value.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            },
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,260 @@
+use super::{
+    error::{AnthropicError, Result},
+    messages::Usage,
+    model::Model,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Mutex, OnceLock, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Struct representing the price of a model, in USD per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ModelPricing {
+    /// Price of input tokens.
+    pub input: f64,
+
+    /// Price of output tokens.
+    pub output: f64,
+
+    /// Price of input tokens written to the prompt cache.
+    pub cache_write: f64,
+
+    /// Price of input tokens read from the prompt cache.
+    pub cache_read: f64,
+}
+
+/// Struct representing a pricing file.
+///
+/// The expected JSON schema maps model ids, as returned by [`Model::id`], to
+/// their prices in USD per million tokens:
+///
+/// ```json
+/// {
+///   "models": {
+///     "claude-sonnet-4-5-20250929": {
+///       "input": 3.0,
+///       "output": 15.0,
+///       "cache_write": 3.75,
+///       "cache_read": 0.3
+///     }
+///   }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct PricingTable {
+    /// Prices by model id.
+    pub models: HashMap<String, ModelPricing>,
+}
+
+impl ModelPricing {
+    /// Prices of Claude 3 Haiku.
+    const HAIKU_3: Self = Self::new(0.25, 1.25, 0.30, 0.03);
+
+    /// Prices of Claude 3.5 Haiku.
+    const HAIKU_35: Self = Self::new(0.80, 4.0, 1.0, 0.08);
+
+    /// Prices of Claude Haiku 4.5.
+    const HAIKU_45: Self = Self::new(1.0, 5.0, 1.25, 0.10);
+
+    /// Prices of the Sonnet models.
+    const SONNET: Self = Self::new(3.0, 15.0, 3.75, 0.30);
+
+    /// Prices of Claude Opus 4 and 4.1.
+    const OPUS_4: Self = Self::new(15.0, 75.0, 18.75, 1.50);
+
+    /// Prices of Claude Opus 4.5.
+    const OPUS_45: Self = Self::new(5.0, 25.0, 6.25, 0.50);
+
+    /// Create a new instance of [`ModelPricing`].
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The price of input tokens.
+    /// * `output` - The price of output tokens.
+    /// * `cache_write` - The price of input tokens written to the prompt cache.
+    /// * `cache_read` - The price of input tokens read from the prompt cache.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`ModelPricing`], in USD per million tokens.
+    #[inline(always)]
+    pub const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Get the built-in prices of a model, as published when this crate was
+    /// released.
+    fn built_in(model: &Model) -> Option<Self> {
+        let pricing = match model {
+            Model::Claude3Haiku => Self::HAIKU_3,
+            Model::Claude35Haiku => Self::HAIKU_35,
+            Model::ClaudeHaiku45 => Self::HAIKU_45,
+            Model::Claude35Sonnet
+            | Model::Claude37Sonnet
+            | Model::ClaudeSonnet4
+            | Model::ClaudeSonnet45 => Self::SONNET,
+            Model::ClaudeOpus4 | Model::ClaudeOpus41 => Self::OPUS_4,
+            Model::ClaudeOpus45 => Self::OPUS_45,
+            Model::Other(_) => return None,
+        };
+
+        Some(pricing)
+    }
+
+    /// Register or override the prices of a model.
+    ///
+    /// This applies process-wide and takes precedence over the built-in prices.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The model id, as returned by [`Model::id`].
+    /// * `pricing` - The prices of the model.
+    pub fn register(id: impl Into<String>, pricing: ModelPricing) {
+        registered()
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(id.into(), pricing);
+    }
+
+    /// Load a pricing file and register every model it lists.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of a JSON file following the [`PricingTable`] schema.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the number of models loaded on success, or an
+    /// [`AnthropicError`] if the file could not be read or parsed.
+    pub fn load_file(path: impl AsRef<Path>) -> Result<usize> {
+        let table: PricingTable = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Self::install(table))
+    }
+
+    /// Fetch a pricing file and register every model it lists.
+    ///
+    /// The fetch is cached: if the same URL was loaded successfully less than
+    /// `max_age` ago, nothing is fetched and the prices already registered are
+    /// kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of a JSON document following the [`PricingTable`] schema.
+    /// * `max_age` - How long a previous fetch of the same URL stays valid.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the number of models loaded, zero when the cache
+    /// was still valid, or an [`AnthropicError`] if the fetch failed.
+    pub async fn load_url(url: &str, max_age: Duration) -> Result<usize> {
+        let is_fresh = fetched()
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(url)
+            .is_some_and(|fetched_at| fetched_at.elapsed() < max_age);
+        if is_fresh {
+            return Ok(0);
+        }
+
+        let response = reqwest::get(url).await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if !status.is_success() {
+            return Err(AnthropicError::from_response(status, &body));
+        }
+
+        let table: PricingTable = serde_json::from_slice(&body)?;
+        let loaded = Self::install(table);
+        fetched()
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(url.to_owned(), Instant::now());
+
+        Ok(loaded)
+    }
+
+    /// Register every model of a pricing table.
+    fn install(table: PricingTable) -> usize {
+        let loaded = table.models.len();
+        registered()
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .extend(table.models);
+
+        loaded
+    }
+}
+
+/// Prices registered at runtime, by model id.
+fn registered() -> &'static RwLock<HashMap<String, ModelPricing>> {
+    static REGISTERED: OnceLock<RwLock<HashMap<String, ModelPricing>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+/// When each pricing URL was last fetched successfully.
+fn fetched() -> &'static Mutex<HashMap<String, Instant>> {
+    static FETCHED: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+    FETCHED.get_or_init(Default::default)
+}
+
+impl Model {
+    /// Get the prices of the model.
+    ///
+    /// # Returns
+    ///
+    /// The registered or loaded prices of the model if any, otherwise its
+    /// built-in prices, or `None` for a model not known to this crate.
+    pub fn pricing(&self) -> Option<ModelPricing> {
+        let registered = registered()
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(self.id())
+            .copied();
+
+        registered.or_else(|| ModelPricing::built_in(self))
+    }
+}
+
+impl Usage {
+    /// Estimate the cost of the usage.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model that handled the request.
+    ///
+    /// # Returns
+    ///
+    /// The cost in USD, or `None` if no prices are known for the model.
+    pub fn cost(&self, model: &Model) -> Option<f64> {
+        let pricing = model.pricing()?;
+        let tokens = [
+            (self.input_tokens, pricing.input),
+            (self.output_tokens, pricing.output),
+            (
+                self.cache_creation_input_tokens.unwrap_or_default(),
+                pricing.cache_write,
+            ),
+            (
+                self.cache_read_input_tokens.unwrap_or_default(),
+                pricing.cache_read,
+            ),
+        ];
+
+        Some(
+            tokens
+                .iter()
+                .map(|(count, price)| *count as f64 * price / 1_000_000.0)
+                .sum(),
+        )
+    }
+}
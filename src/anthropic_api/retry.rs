@@ -0,0 +1,237 @@
+use reqwest::header::HeaderMap;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Default number of retries of a failed request.
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Default delay before the first retry.
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Default ceiling of the exponential backoff.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Default ceiling of a delay requested by the server through `retry-after`.
+pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// [`RetryPolicy`] struct deciding how long to wait before retrying a request.
+///
+/// Two delays are distinguished. When the server says how long to wait, with
+/// the `retry-after` header of a rate limited response, its value wins even
+/// above `max_delay`, only capped by `max_retry_after` to avoid pathological
+/// waits. Otherwise, e.g. for network errors or overloaded responses, an
+/// exponential backoff with jitter capped by `max_delay` is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries of a request.
+    max_retries: u32,
+
+    /// Delay before the first retry, doubled on every following retry.
+    base_delay: Duration,
+
+    /// Ceiling of the exponential backoff.
+    max_delay: Duration,
+
+    /// Ceiling of a delay requested by the server.
+    max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_retry_after: DEFAULT_MAX_RETRY_AFTER,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new instance of [`RetryPolicy`].
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`RetryPolicy`] with the default limits.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of retries of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_retries` - How many times a failed request is retried.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RetryPolicy`].
+    #[inline(always)]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_delay` - The delay before the first retry, doubled on every
+    ///   following retry.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RetryPolicy`].
+    #[inline(always)]
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the ceiling of the exponential backoff.
+    ///
+    /// This does not apply to delays requested by the server, see
+    /// [`RetryPolicy::max_retry_after`].
+    ///
+    /// # Arguments
+    ///
+    /// * `max_delay` - The longest delay between two attempts.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RetryPolicy`].
+    #[inline(always)]
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Set the ceiling of a delay requested by the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_retry_after` - The longest `retry-after` delay honored.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RetryPolicy`].
+    #[inline(always)]
+    pub fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Get the maximum number of retries of a request.
+    #[inline(always)]
+    pub fn retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Get the delay to wait before a retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The number of retries already made, zero for the first.
+    /// * `retry_after` - The delay requested by the server, if any.
+    ///
+    /// # Returns
+    ///
+    /// The requested delay capped by `max_retry_after` if the server sent one,
+    /// otherwise the exponential backoff for `attempt`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(retry_after) => retry_after.min(self.max_retry_after),
+            None => self.backoff(attempt),
+        }
+    }
+
+    /// Get the exponential backoff before a retry.
+    ///
+    /// The delay doubles with every attempt up to `max_delay`, and a random
+    /// jitter of up to half of it is subtracted so that clients failing
+    /// together do not retry together.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The number of retries already made, zero for the first.
+    ///
+    /// # Returns
+    ///
+    /// The delay to wait, between half of and the full capped backoff.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let jitter = delay.mul_f64(random_fraction() / 2.0);
+
+        delay - jitter
+    }
+}
+
+/// Read the delay requested by the server from the headers of a response.
+///
+/// The millisecond precision `retry-after-ms` header is preferred, then
+/// `retry-after` in seconds. HTTP dates are not supported and are ignored.
+///
+/// # Arguments
+///
+/// * `headers` - The headers of the response.
+///
+/// # Returns
+///
+/// The requested delay, or `None` if the response does not carry one.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value >= 0.0)
+    };
+
+    header("retry-after-ms")
+        .map(|millis| Duration::from_secs_f64(millis / 1000.0))
+        .or_else(|| header("retry-after").map(Duration::from_secs_f64))
+}
+
+/// Generate a random idempotency key, formatted as a version 4 UUID.
+///
+/// A key identifies one logical request: every retry of the request carries
+/// the same key, so that a server honoring the `idempotency-key` header
+/// performs it at most once.
+///
+/// # Returns
+///
+/// A new key, e.g. `0b8f5c1e-3a4d-4e2f-9c7b-5d6e7f8a9b0c`.
+pub fn idempotency_key() -> String {
+    let high = RandomState::new().build_hasher().finish();
+    let low = RandomState::new().build_hasher().finish();
+    // Set the version (4) and variant (RFC 4122) bits.
+    let random = (u128::from(high) << 64 | u128::from(low)) & !(0xf << 76 | 0x3 << 62)
+        | 0x4 << 76
+        | 0x2 << 62;
+
+    let hex = format!("{random:032x}");
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Get a random number in `[0, 1)`.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
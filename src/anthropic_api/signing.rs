@@ -0,0 +1,125 @@
+use ring::hmac;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default name of the header carrying the signature.
+pub const DEFAULT_SIGNATURE_HEADER: &str = "x-signature";
+
+/// Default name of the header carrying the timestamp of the signature.
+pub const DEFAULT_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+/// [`RequestSigner`] struct signing requests for gateways using HMAC authentication.
+///
+/// Each request is signed with HMAC-SHA256 over the string
+/// `{timestamp}\n{method}\n{path}\n{body}`, where `timestamp` is the number of
+/// seconds since the Unix epoch and `path` the path of the request URL. The
+/// hex encoded signature and the timestamp are sent in their own headers, in
+/// addition to the usual API key header.
+#[derive(Clone)]
+pub struct RequestSigner {
+    /// Key derived from the shared secret.
+    key: hmac::Key,
+
+    /// Name of the header carrying the signature.
+    signature_header: String,
+
+    /// Name of the header carrying the timestamp.
+    timestamp_header: String,
+}
+
+impl RequestSigner {
+    /// Create a new instance of [`RequestSigner`].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret shared with the gateway.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`RequestSigner`] using the default header names.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref()),
+            signature_header: DEFAULT_SIGNATURE_HEADER.to_owned(),
+            timestamp_header: DEFAULT_TIMESTAMP_HEADER.to_owned(),
+        }
+    }
+
+    /// Set the name of the header carrying the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature_header` - The header name expected by the gateway.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RequestSigner`].
+    #[inline(always)]
+    pub fn signature_header(mut self, signature_header: impl Into<String>) -> Self {
+        self.signature_header = signature_header.into();
+        self
+    }
+
+    /// Set the name of the header carrying the timestamp of the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp_header` - The header name expected by the gateway.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`RequestSigner`].
+    #[inline(always)]
+    pub fn timestamp_header(mut self, timestamp_header: impl Into<String>) -> Self {
+        self.timestamp_header = timestamp_header.into();
+        self
+    }
+
+    /// Sign a request made now.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the request.
+    /// * `path` - The path of the request URL.
+    /// * `body` - The serialized request body.
+    ///
+    /// # Returns
+    ///
+    /// The headers to add to the request, as name and value pairs.
+    pub fn sign(&self, method: &str, path: &str, body: &[u8]) -> Vec<(String, String)> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let signature = self.signature(timestamp, method, path, body);
+
+        vec![
+            (self.timestamp_header.clone(), timestamp.to_string()),
+            (self.signature_header.clone(), signature),
+        ]
+    }
+
+    /// Compute the hex encoded signature of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The time of the signature, in seconds since the Unix epoch.
+    /// * `method` - The HTTP method of the request.
+    /// * `path` - The path of the request URL.
+    /// * `body` - The serialized request body.
+    ///
+    /// # Returns
+    ///
+    /// The signature as lowercase hexadecimal.
+    pub fn signature(&self, timestamp: u64, method: &str, path: &str, body: &[u8]) -> String {
+        let mut context = hmac::Context::with_key(&self.key);
+        context.update(format!("{timestamp}\n{method}\n{path}\n").as_bytes());
+        context.update(body);
+
+        context
+            .sign()
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
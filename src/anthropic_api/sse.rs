@@ -0,0 +1,194 @@
+use super::{
+    error::{AnthropicError, Result},
+    streaming::DEFAULT_MAX_FRAME_SIZE,
+};
+
+/// Struct representing a single server-sent event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SseFrame {
+    /// Name of the event, empty if not set.
+    pub(crate) event: String,
+
+    /// Data of the event, with multiple `data` lines joined by newlines.
+    pub(crate) data: String,
+}
+
+/// Struct decoding a `text/event-stream` body into [`SseFrame`]s.
+///
+/// Bytes are fed in arbitrary chunks, so a frame or even a single line may
+/// span several chunks; incomplete lines are kept until the rest arrives, up
+/// to a maximum frame size protecting against unbounded memory growth.
+///
+/// The decoder is built for many concurrent streams: a single growable buffer
+/// is reused for the whole stream, lines are decoded in place, and frames are
+/// lent out rather than returned by value so their strings keep their
+/// capacity from one frame to the next. Once warmed up, decoding a frame does
+/// not allocate.
+pub(crate) struct SseDecoder {
+    /// Maximum size of a single frame, in bytes.
+    max_frame_size: usize,
+
+    /// Bytes received, of which those before `start` were already decoded.
+    buffer: Vec<u8>,
+
+    /// Position of the first byte of `buffer` not yet split into lines.
+    start: usize,
+
+    /// The frame being assembled from the lines read so far.
+    frame: SseFrame,
+
+    /// Whether the frame being assembled has any field set.
+    has_fields: bool,
+
+    /// Whether the frame being assembled has a `data` line.
+    has_data: bool,
+
+    /// Whether `frame` was lent out and must be reset before reuse.
+    lent: bool,
+}
+
+impl Default for SseDecoder {
+    fn default() -> Self {
+        Self {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            buffer: Vec::new(),
+            start: 0,
+            frame: SseFrame::default(),
+            has_fields: false,
+            has_data: false,
+            lent: false,
+        }
+    }
+}
+
+impl SseDecoder {
+    /// Set the maximum size of a single frame, in bytes.
+    pub(crate) fn set_max_frame_size(&mut self, max_frame_size: usize) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// Append a chunk of the body.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) {
+        // Drop the decoded bytes first so the buffer does not grow with the
+        // whole stream; this only moves the incomplete tail, if any.
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Decode the next complete frame, if one is available.
+    ///
+    /// The frame is lent until the next call, which reuses its storage.
+    ///
+    /// Fails with [`AnthropicError::FrameTooLarge`] once the frame being
+    /// assembled, including its incomplete last line, exceeds the maximum
+    /// frame size.
+    pub(crate) fn next_frame(&mut self) -> Result<Option<&SseFrame>> {
+        if self.lent {
+            self.frame.event.clear();
+            self.frame.data.clear();
+            self.lent = false;
+        }
+
+        while let Some(offset) = self.buffer[self.start..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+        {
+            let line = &self.buffer[self.start..self.start + offset];
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            self.start += offset + 1;
+
+            if line.is_empty() {
+                if self.has_fields {
+                    self.has_fields = false;
+                    self.has_data = false;
+                    self.lent = true;
+                    return Ok(Some(&self.frame));
+                }
+                continue;
+            }
+
+            let line = String::from_utf8_lossy(line);
+            if self.frame.read_line(&line, &mut self.has_data) {
+                self.has_fields = true;
+            }
+            self.check_size(0)?;
+        }
+
+        // Only the incomplete last line of the frame is left in the buffer;
+        // complete frames after the current one were decoded already.
+        self.check_size(self.buffer.len() - self.start)?;
+        Ok(None)
+    }
+
+    /// Fail if the frame being assembled, with `partial_line` more bytes of
+    /// its incomplete last line, exceeds the maximum frame size.
+    fn check_size(&self, partial_line: usize) -> Result<()> {
+        if self.frame.data.len() + partial_line > self.max_frame_size {
+            return Err(AnthropicError::FrameTooLarge {
+                max_frame_size: self.max_frame_size,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Decode the last frame once the body ended, even if it was not
+    /// terminated by a blank line.
+    pub(crate) fn finish(&mut self) -> Result<Option<&SseFrame>> {
+        if self.start < self.buffer.len() {
+            self.buffer.push(b'\n');
+        }
+        self.buffer.push(b'\n');
+        self.next_frame()
+    }
+
+    /// Discard any buffered bytes and partial frame.
+    pub(crate) fn clear(&mut self) {
+        self.buffer.clear();
+        self.start = 0;
+        self.frame.event.clear();
+        self.frame.data.clear();
+        self.has_fields = false;
+        self.has_data = false;
+        self.lent = false;
+    }
+}
+
+impl SseFrame {
+    /// Apply a single non-empty line to the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line, without its line terminator.
+    /// * `has_data` - Whether the frame already has a `data` line, updated.
+    ///
+    /// # Returns
+    ///
+    /// Whether the line set a field of the frame.
+    fn read_line(&mut self, line: &str, has_data: &mut bool) -> bool {
+        if line.starts_with(':') {
+            return false;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+
+        match field {
+            "event" => {
+                self.event.clear();
+                self.event.push_str(value);
+            },
+            "data" => {
+                if *has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                *has_data = true;
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
@@ -0,0 +1,1104 @@
+use super::{
+    client::StreamGuard,
+    content::{ContentBlock, ToolUse},
+    error::{AnthropicError, Result},
+    messages::{Container, MessageResponse, StopReason, Usage},
+    sse::SseDecoder,
+    tokens::estimate_tokens,
+};
+use bytes::Bytes;
+use futures_core::Stream;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::OwnedSemaphorePermit,
+    time::{self, Sleep},
+};
+
+/// Default maximum size of a single server-sent event, in bytes.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Body of a streaming response, as a stream of byte chunks.
+type ByteStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// Enum representing an event received while streaming a message.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// Start of the message, carrying the message with empty content.
+    MessageStart {
+        /// The message being streamed.
+        message: MessageResponse,
+    },
+
+    /// Start of a content block.
+    ContentBlockStart {
+        /// Index of the content block in the message.
+        index: usize,
+
+        /// Initial state of the content block.
+        content_block: Value,
+    },
+
+    /// Incremental update of a content block.
+    ContentBlockDelta {
+        /// Index of the content block in the message.
+        index: usize,
+
+        /// Update to apply to the content block.
+        delta: Value,
+    },
+
+    /// End of a content block.
+    ContentBlockStop {
+        /// Index of the content block in the message.
+        index: usize,
+    },
+
+    /// Top-level update of the message.
+    MessageDelta {
+        /// Updated top-level fields of the message.
+        delta: MessageDelta,
+
+        /// Cumulative token usage of the message so far.
+        usage: DeltaUsage,
+    },
+
+    /// End of the message.
+    MessageStop,
+
+    /// Keep-alive event.
+    Ping,
+
+    /// Error reported in the middle of the stream.
+    Error {
+        /// The error object.
+        error: Value,
+    },
+
+    /// Any event type not yet known to this crate.
+    ///
+    /// The API may add event types at any time; [`MessageStream`] skips them
+    /// instead of yielding them, and [`MessageAccumulator`] ignores them.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Struct representing the top-level fields updated by a `message_delta` event.
+///
+/// The event is sent once the last content block ended, so these fields hold
+/// the final state of the message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MessageDelta {
+    /// Reason the model stopped generating.
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+
+    /// Stop sequence that was generated, if any.
+    #[serde(default)]
+    pub stop_sequence: Option<String>,
+
+    /// Code execution container used by the request, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<Container>,
+}
+
+/// Struct representing the usage carried by a `message_delta` event.
+///
+/// All counts are cumulative for the whole message, not increments since the
+/// previous event. Input and cache counts are only present when the server
+/// updated them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeltaUsage {
+    /// Number of output tokens generated so far.
+    pub output_tokens: u64,
+
+    /// Number of input tokens that were processed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+
+    /// Number of input tokens written to the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+
+    /// Number of input tokens read from the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+impl Usage {
+    /// Apply the cumulative usage of a `message_delta` event.
+    ///
+    /// Output tokens are replaced rather than added since the delta already
+    /// counts every token generated so far. Input and cache counts are only
+    /// replaced when the delta carries them, keeping the `message_start` values
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The usage of the `message_delta` event.
+    pub fn apply_delta(&mut self, delta: &DeltaUsage) {
+        self.output_tokens = delta.output_tokens;
+        if let Some(input_tokens) = delta.input_tokens {
+            self.input_tokens = input_tokens;
+        }
+        if delta.cache_creation_input_tokens.is_some() {
+            self.cache_creation_input_tokens = delta.cache_creation_input_tokens;
+        }
+        if delta.cache_read_input_tokens.is_some() {
+            self.cache_read_input_tokens = delta.cache_read_input_tokens;
+        }
+    }
+}
+
+/// Struct counting the events of a stream by type, for diagnostics.
+///
+/// A stream behaving unexpectedly shows up in the counts, e.g. a missing
+/// `message_stop` or a run of pings without any delta.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EventStats {
+    /// Number of `message_start` events.
+    pub message_start: u64,
+
+    /// Number of `content_block_start` events.
+    pub content_block_start: u64,
+
+    /// Number of `content_block_delta` events.
+    pub content_block_delta: u64,
+
+    /// Number of `content_block_stop` events.
+    pub content_block_stop: u64,
+
+    /// Number of `message_delta` events.
+    pub message_delta: u64,
+
+    /// Number of `message_stop` events.
+    pub message_stop: u64,
+
+    /// Number of `ping` events.
+    pub ping: u64,
+
+    /// Number of `error` events.
+    pub error: u64,
+
+    /// Number of events of a type unknown to this crate, which were skipped.
+    pub unknown: u64,
+}
+
+impl EventStats {
+    /// Get the total number of events counted.
+    pub fn total(&self) -> u64 {
+        self.message_start
+            + self.content_block_start
+            + self.content_block_delta
+            + self.content_block_stop
+            + self.message_delta
+            + self.message_stop
+            + self.ping
+            + self.error
+            + self.unknown
+    }
+
+    /// Count an event.
+    fn record(&mut self, event: &StreamEvent) {
+        let count = match event {
+            StreamEvent::MessageStart { .. } => &mut self.message_start,
+            StreamEvent::ContentBlockStart { .. } => &mut self.content_block_start,
+            StreamEvent::ContentBlockDelta { .. } => &mut self.content_block_delta,
+            StreamEvent::ContentBlockStop { .. } => &mut self.content_block_stop,
+            StreamEvent::MessageDelta { .. } => &mut self.message_delta,
+            StreamEvent::MessageStop => &mut self.message_stop,
+            StreamEvent::Ping => &mut self.ping,
+            StreamEvent::Error { .. } => &mut self.error,
+            StreamEvent::Unknown => &mut self.unknown,
+        };
+        *count += 1;
+    }
+}
+
+/// [`MessageAccumulator`] struct assembling stream events into a full message.
+#[derive(Default)]
+pub struct MessageAccumulator {
+    /// The message assembled so far, once `message_start` was received.
+    message: Option<MessageResponse>,
+
+    /// Partial JSON input of tool use blocks, by content block index.
+    partial_json: Vec<(usize, String)>,
+
+    /// Indices of the content blocks started but not stopped yet.
+    open_blocks: Vec<usize>,
+
+    /// Counts of the events pushed, by type.
+    stats: EventStats,
+}
+
+impl MessageAccumulator {
+    /// Create a new instance of [`MessageAccumulator`].
+    ///
+    /// # Returns
+    ///
+    /// A new, empty instance of [`MessageAccumulator`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a stream event to the message assembled so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The next event of the stream.
+    pub fn push(&mut self, event: &StreamEvent) {
+        self.stats.record(event);
+
+        match event {
+            StreamEvent::MessageStart { message } => self.message = Some(message.clone()),
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                if let Some(message) = &mut self.message {
+                    if message.content.len() <= *index {
+                        message
+                            .content
+                            .resize(*index + 1, ContentBlock::Unknown(Value::Null));
+                    }
+                    message.content[*index] = ContentBlock::deserialize(content_block)
+                        .unwrap_or_else(|_| ContentBlock::Unknown(content_block.clone()));
+                }
+                self.open_blocks.push(*index);
+            },
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                self.apply_block_delta(*index, delta)
+            },
+            StreamEvent::ContentBlockStop { index } => {
+                self.open_blocks.retain(|open| open != index);
+                self.finish_block(*index);
+            },
+            StreamEvent::MessageDelta { delta, usage } => {
+                if let Some(message) = &mut self.message {
+                    if delta.stop_reason.is_some() {
+                        message.stop_reason = delta.stop_reason;
+                    }
+                    if delta.stop_sequence.is_some() {
+                        message.stop_sequence.clone_from(&delta.stop_sequence);
+                    }
+                    if delta.container.is_some() {
+                        message.container.clone_from(&delta.container);
+                    }
+                    message.usage.apply_delta(usage);
+                }
+            },
+            StreamEvent::MessageStop
+            | StreamEvent::Ping
+            | StreamEvent::Error { .. }
+            | StreamEvent::Unknown => {},
+        }
+    }
+
+    /// Get the message assembled so far.
+    #[inline(always)]
+    pub fn message(&self) -> Option<&MessageResponse> {
+        self.message.as_ref()
+    }
+
+    /// Get the counts of the events pushed so far, by type.
+    #[inline(always)]
+    pub fn stats(&self) -> EventStats {
+        self.stats
+    }
+
+    /// Get the tool calls of the blocks completed so far.
+    ///
+    /// This is the streaming counterpart of [`MessageResponse::tool_uses`]:
+    /// the input of each call is parsed from its JSON deltas once its block
+    /// stopped, so calls still being streamed are left out. Once the stream
+    /// ended, every call is included whichever order their deltas arrived in,
+    /// and [`MessageAccumulator::finish`] returns a message with the same calls.
+    ///
+    /// # Returns
+    ///
+    /// The tool calls of the completed `tool_use` blocks, in content order.
+    pub fn tool_uses(&self) -> Vec<ToolUse> {
+        let Some(message) = &self.message else {
+            return Vec::new();
+        };
+
+        message
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.open_blocks.contains(index))
+            .filter_map(|(_, block)| match block {
+                ContentBlock::ToolUse(tool_use) => Some(tool_use.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Finish accumulating and return the assembled message.
+    ///
+    /// # Returns
+    ///
+    /// The assembled [`MessageResponse`], or `None` if no `message_start` event
+    /// was received.
+    pub fn finish(mut self) -> Option<MessageResponse> {
+        let pending: Vec<usize> = self.partial_json.iter().map(|(index, _)| *index).collect();
+        for index in pending {
+            self.finish_block(index);
+        }
+
+        self.message
+    }
+
+    /// Apply a `content_block_delta` to the block at `index`.
+    fn apply_block_delta(&mut self, index: usize, delta: &Value) {
+        let Some(block) = self
+            .message
+            .as_mut()
+            .and_then(|message| message.content.get_mut(index))
+        else {
+            return;
+        };
+
+        let text = |key: &str| delta[key].as_str().unwrap_or_default();
+        match (delta["type"].as_str(), block) {
+            (Some("text_delta"), ContentBlock::Text { text: current }) => {
+                current.push_str(text("text"));
+            },
+            (Some("thinking_delta"), ContentBlock::Thinking { thinking, .. }) => {
+                thinking.push_str(text("thinking"));
+            },
+            (Some("signature_delta"), ContentBlock::Thinking { signature, .. }) => {
+                text("signature").clone_into(signature);
+            },
+            (Some("input_json_delta"), _) => {
+                let partial = text("partial_json");
+                match self.partial_json.iter_mut().find(|(i, _)| *i == index) {
+                    Some((_, json)) => json.push_str(partial),
+                    None => self.partial_json.push((index, partial.to_owned())),
+                }
+            },
+            (Some("text_delta"), ContentBlock::Unknown(block)) => {
+                append_str(block, "text", &delta["text"]);
+            },
+            (Some("thinking_delta"), ContentBlock::Unknown(block)) => {
+                append_str(block, "thinking", &delta["thinking"]);
+            },
+            (Some("signature_delta"), ContentBlock::Unknown(block)) => {
+                block["signature"] = delta["signature"].clone();
+            },
+            _ => {},
+        }
+    }
+
+    /// Parse the accumulated JSON input of the block at `index`, if any.
+    fn finish_block(&mut self, index: usize) {
+        let Some(position) = self.partial_json.iter().position(|(i, _)| *i == index) else {
+            return;
+        };
+        let (_, json) = self.partial_json.swap_remove(position);
+
+        if let Some(block) = self
+            .message
+            .as_mut()
+            .and_then(|message| message.content.get_mut(index))
+        {
+            if json.is_empty() {
+                return;
+            }
+            let input = serde_json::from_str(&json).unwrap_or(Value::String(json));
+            match block {
+                ContentBlock::ToolUse(tool_use) => tool_use.input = input,
+                ContentBlock::Unknown(block) => block["input"] = input,
+                _ => {},
+            }
+        }
+    }
+}
+
+/// Build the error for an `error` event received mid-stream.
+///
+/// The response status was already a success, so the status is derived from
+/// the error type, keeping overloaded and rate limited errors retryable.
+fn stream_error(error: Value) -> AnthropicError {
+    let status = match error["type"].as_str() {
+        Some("invalid_request_error") => StatusCode::BAD_REQUEST,
+        Some("rate_limit_error") => StatusCode::TOO_MANY_REQUESTS,
+        Some("overloaded_error") => StatusCode::from_u16(529).unwrap_or_default(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = serde_json::json!({ "type": "error", "error": error }).to_string();
+
+    AnthropicError::from_response(status, body.as_bytes())
+}
+
+/// Append the string `addition` to the string field `key` of `block`.
+fn append_str(block: &mut Value, key: &str, addition: &Value) {
+    let addition = addition.as_str().unwrap_or_default();
+    match &mut block[key] {
+        Value::String(current) => current.push_str(addition),
+        field => *field = Value::String(addition.to_owned()),
+    }
+}
+
+/// Struct representing the timing breakdown of a stream.
+///
+/// Measured only when [`AnthropicClient::debug_timing`] is enabled. Tokens
+/// are counted as they arrive in content block deltas, so a delta carrying
+/// several tokens counts once.
+///
+/// [`AnthropicClient::debug_timing`]: super::client::AnthropicClient::debug_timing
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamTiming {
+    /// Time from sending the request to receiving the response headers.
+    pub time_to_first_byte: Duration,
+
+    /// Time from sending the request to the first content delta, if any.
+    pub time_to_first_token: Option<Duration>,
+
+    /// Time between each content delta and the previous one, in order.
+    pub inter_token_latencies: Vec<Duration>,
+
+    /// Time from sending the request to the end of the body, once it ended.
+    pub total: Option<Duration>,
+}
+
+impl StreamTiming {
+    /// Get the mean time between two content deltas, if there were several.
+    pub fn mean_inter_token_latency(&self) -> Option<Duration> {
+        let count = u32::try_from(self.inter_token_latencies.len()).ok()?;
+        (count > 0).then(|| self.inter_token_latencies.iter().sum::<Duration>() / count)
+    }
+}
+
+/// Struct measuring the timing of a stream as its events arrive.
+struct TimingRecorder {
+    /// When the request was sent.
+    start: Instant,
+
+    /// When the last content delta arrived.
+    last_token: Option<Instant>,
+
+    /// The timing measured so far.
+    timing: StreamTiming,
+}
+
+impl TimingRecorder {
+    /// Record the arrival of a content delta.
+    fn token(&mut self) {
+        let now = Instant::now();
+        match self.last_token {
+            Some(last_token) => self.timing.inter_token_latencies.push(now - last_token),
+            None => self.timing.time_to_first_token = Some(now - self.start),
+        }
+        self.last_token = Some(now);
+    }
+
+    /// Record the end of the body.
+    fn end(&mut self) {
+        self.timing
+            .total
+            .get_or_insert_with(|| self.start.elapsed());
+    }
+}
+
+/// [`MessageStream`] struct yielding the events of a streamed message.
+///
+/// Every event is also applied to an internal [`MessageAccumulator`], so the
+/// message assembled so far is available at any point through
+/// [`MessageStream::message`].
+pub struct MessageStream {
+    /// Body of the response, `None` once it ended or was aborted.
+    body: Option<ByteStream>,
+
+    /// Decoder splitting the body into server-sent events.
+    decoder: SseDecoder,
+
+    /// Accumulator assembling the message from the events.
+    accumulator: MessageAccumulator,
+
+    /// Number of output tokens after which the stream is aborted.
+    token_budget: Option<u64>,
+
+    /// Estimated number of output tokens generated so far.
+    output_tokens: u64,
+
+    /// Whether the stream was aborted because the token budget was reached.
+    budget_cutoff: bool,
+
+    /// Slot held in the client's stream limit until the body ends.
+    permit: Option<OwnedSemaphorePermit>,
+
+    /// Registration of the stream as in flight on the client until the body
+    /// ends.
+    guard: Option<StreamGuard>,
+
+    /// Timing of the stream, when measured.
+    timing: Option<TimingRecorder>,
+}
+
+impl MessageStream {
+    /// Create a new instance of [`MessageStream`] reading a response body.
+    pub(crate) fn new(
+        response: reqwest::Response,
+        permit: Option<OwnedSemaphorePermit>,
+        guard: StreamGuard,
+    ) -> Self {
+        Self {
+            body: Some(Box::pin(response.bytes_stream())),
+            decoder: SseDecoder::default(),
+            accumulator: MessageAccumulator::new(),
+            token_budget: None,
+            output_tokens: 0,
+            budget_cutoff: false,
+            permit,
+            guard: Some(guard),
+            timing: None,
+        }
+    }
+
+    /// Measure the timing of the stream, from a request sent at `start`.
+    pub(crate) fn timed(mut self, start: Instant, time_to_first_byte: Duration) -> Self {
+        self.timing = Some(TimingRecorder {
+            start,
+            last_token: None,
+            timing: StreamTiming {
+                time_to_first_byte,
+                ..Default::default()
+            },
+        });
+        self
+    }
+
+    /// Set a hard budget of output tokens for the stream.
+    ///
+    /// Once the output tokens generated reach the budget, the request is
+    /// aborted and the stream ends after the event that reached it, even if the
+    /// model would have continued. This is a runtime cap on top of
+    /// `max_tokens`. Output tokens are counted from the usage reported by the
+    /// server when available and estimated from the received deltas otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_budget` - The maximum number of output tokens to receive.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageStream`].
+    #[inline(always)]
+    pub fn token_budget(mut self, token_budget: u64) -> Self {
+        self.token_budget = Some(token_budget);
+        self
+    }
+
+    /// Set the maximum size of a single server-sent event, in bytes.
+    ///
+    /// A server sending a larger frame, or a line that never ends, makes the
+    /// stream fail with [`AnthropicError::FrameTooLarge`] instead of buffering
+    /// it without bound. Defaults to [`DEFAULT_MAX_FRAME_SIZE`].
+    ///
+    /// [`AnthropicError::FrameTooLarge`]: super::error::AnthropicError::FrameTooLarge
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame size, in bytes.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`MessageStream`].
+    #[inline(always)]
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.decoder.set_max_frame_size(max_frame_size);
+        self
+    }
+
+    /// Whether the stream was aborted because the token budget was reached.
+    ///
+    /// When this is `true`, the message returned by [`MessageStream::message`]
+    /// is partial and has no stop reason.
+    #[inline(always)]
+    pub fn budget_cutoff(&self) -> bool {
+        self.budget_cutoff
+    }
+
+    /// Get the estimated number of output tokens received so far.
+    #[inline(always)]
+    pub fn output_tokens(&self) -> u64 {
+        self.output_tokens
+    }
+
+    /// Get the message assembled from the events received so far.
+    #[inline(always)]
+    pub fn message(&self) -> Option<&MessageResponse> {
+        self.accumulator.message()
+    }
+
+    /// Get the counts of the events received so far, by type.
+    ///
+    /// Events that failed to parse are not counted. The counts remain
+    /// available after the stream ended, e.g. through
+    /// [`CoalescedStream::into_inner`].
+    #[inline(always)]
+    pub fn stats(&self) -> EventStats {
+        self.accumulator.stats()
+    }
+
+    /// Get the timing of the stream so far.
+    ///
+    /// # Returns
+    ///
+    /// The [`StreamTiming`] measured so far, or `None` unless
+    /// [`AnthropicClient::debug_timing`] is enabled.
+    ///
+    /// [`AnthropicClient::debug_timing`]: super::client::AnthropicClient::debug_timing
+    #[inline(always)]
+    pub fn timing(&self) -> Option<&StreamTiming> {
+        self.timing.as_ref().map(|recorder| &recorder.timing)
+    }
+
+    /// Stop reading and return the message assembled so far.
+    ///
+    /// # Returns
+    ///
+    /// The assembled [`MessageResponse`], or `None` if no `message_start` event
+    /// was received.
+    #[inline(always)]
+    pub fn into_message(self) -> Option<MessageResponse> {
+        self.accumulator.finish()
+    }
+
+    /// Read the rest of the stream and return the complete message.
+    ///
+    /// This suits callers streaming for latency who still need the final
+    /// message object: content blocks are assembled by index whichever order
+    /// their deltas arrive in, tool inputs are parsed from their JSON deltas,
+    /// and the usage holds the final output tokens of the `message_delta`
+    /// event. When the token budget cut the stream, the partial message is
+    /// returned without a stop reason.
+    ///
+    /// # Returns
+    ///
+    /// A [`Result`] containing the assembled [`MessageResponse`] on success,
+    /// an [`AnthropicError::Api`] if the server reported an error mid-stream,
+    /// or an [`AnthropicError::IncompleteStream`] if the stream ended before
+    /// `message_stop`.
+    pub async fn collect_message(mut self) -> Result<MessageResponse> {
+        while let Some(event) = std::future::poll_fn(|cx| Pin::new(&mut self).poll_next(cx)).await {
+            if let StreamEvent::Error { error } = event? {
+                return Err(stream_error(error));
+            }
+        }
+
+        let complete = self.budget_cutoff || self.stats().message_stop > 0;
+        match self.into_message() {
+            Some(message) if complete => Ok(message),
+            _ => Err(AnthropicError::IncompleteStream),
+        }
+    }
+
+    /// Batch the text of the stream by time window.
+    ///
+    /// Text deltas are accumulated and emitted together once `interval` has
+    /// passed since the first delta of the batch, which suits interfaces that
+    /// refresh on a fixed cadence rather than per token. Whatever is pending
+    /// is flushed when the stream ends or fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - How long text is accumulated before being emitted.
+    ///
+    /// # Returns
+    ///
+    /// A [`CoalescedStream`] of text batches.
+    #[inline(always)]
+    pub fn coalesce(self, interval: Duration) -> CoalescedStream {
+        CoalescedStream {
+            inner: self,
+            interval,
+            pending: String::new(),
+            deadline: None,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Emit the text of the stream one sentence at a time.
+    ///
+    /// Text deltas are buffered until a sentence is complete, which suits
+    /// text-to-speech engines that sound choppy when fed partial phrases. A
+    /// sentence ends with `.`, `!` or `?`, possibly followed by closing quotes
+    /// or brackets, then whitespace; so decimals such as `3.14` and common
+    /// abbreviations and initials such as `Dr.` or `J.` do not end one. The
+    /// trailing partial sentence is flushed when the stream ends or fails.
+    ///
+    /// # Returns
+    ///
+    /// A [`SentenceStream`] of sentences, without surrounding whitespace.
+    #[inline(always)]
+    pub fn sentence_chunks(self) -> SentenceStream {
+        SentenceStream {
+            inner: self,
+            pending: String::new(),
+            sentences: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Stop reading the body, discarding anything buffered.
+    fn abort(&mut self) {
+        self.body = None;
+        self.permit = None;
+        self.guard = None;
+        self.decoder.clear();
+    }
+
+    /// Apply a decoded event to the stream state.
+    fn observe(&mut self, event: &StreamEvent) {
+        self.accumulator.push(event);
+
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.output_tokens = message.usage.output_tokens;
+            },
+            StreamEvent::ContentBlockDelta { delta, .. } => {
+                if let Some(timing) = &mut self.timing {
+                    timing.token();
+                }
+                let text = ["text", "thinking", "partial_json"]
+                    .iter()
+                    .find_map(|key| delta[*key].as_str())
+                    .unwrap_or_default();
+                self.output_tokens += estimate_tokens(text);
+            },
+            StreamEvent::MessageDelta { usage, .. } => {
+                self.output_tokens = self.output_tokens.max(usage.output_tokens);
+            },
+            _ => {},
+        }
+
+        if self
+            .token_budget
+            .is_some_and(|budget| self.output_tokens >= budget)
+            && !matches!(event, StreamEvent::MessageStop)
+        {
+            self.budget_cutoff = true;
+            self.abort();
+        }
+    }
+}
+
+impl Stream for MessageStream {
+    type Item = Result<StreamEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this
+            .guard
+            .as_mut()
+            .is_some_and(|guard| guard.poll_cancelled(cx).is_ready())
+        {
+            this.abort();
+            return Poll::Ready(Some(Err(AnthropicError::Cancelled)));
+        }
+
+        loop {
+            let frame = match this.body {
+                Some(_) => this.decoder.next_frame(),
+                None => this.decoder.finish(),
+            };
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(error) => {
+                    this.abort();
+                    return Poll::Ready(Some(Err(error)));
+                },
+            };
+            if let Some(frame) = frame {
+                let event: StreamEvent = match serde_json::from_str(&frame.data) {
+                    Ok(event) => event,
+                    Err(error) => return Poll::Ready(Some(Err(error.into()))),
+                };
+                this.observe(&event);
+                if matches!(event, StreamEvent::Unknown) {
+                    continue;
+                }
+                return Poll::Ready(Some(Ok(event)));
+            }
+
+            let Some(body) = &mut this.body else {
+                this.permit = None;
+                this.guard = None;
+                return Poll::Ready(None);
+            };
+            match body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.decoder.feed(&chunk),
+                Poll::Ready(None) => {
+                    this.body = None;
+                    if let Some(timing) = &mut this.timing {
+                        timing.end();
+                    }
+                },
+                Poll::Ready(Some(Err(error))) => {
+                    this.abort();
+                    return Poll::Ready(Some(Err(error.into())));
+                },
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// [`CoalescedStream`] struct emitting the text of a [`MessageStream`] in
+/// batches, created by [`MessageStream::coalesce`].
+pub struct CoalescedStream {
+    /// The underlying stream of events.
+    inner: MessageStream,
+
+    /// How long text is accumulated before being emitted.
+    interval: Duration,
+
+    /// Text received since the last batch.
+    pending: String,
+
+    /// When the pending text is emitted, set by the first delta of a batch.
+    deadline: Option<Pin<Box<Sleep>>>,
+
+    /// Error to emit once the pending text was flushed.
+    error: Option<AnthropicError>,
+
+    /// Whether the underlying stream ended.
+    done: bool,
+}
+
+impl CoalescedStream {
+    /// Get the message assembled so far.
+    #[inline(always)]
+    pub fn message(&self) -> Option<&MessageResponse> {
+        self.inner.message()
+    }
+
+    /// Get the underlying [`MessageStream`], dropping any pending text.
+    #[inline(always)]
+    pub fn into_inner(self) -> MessageStream {
+        self.inner
+    }
+
+    /// Take the pending text as a batch, if any.
+    fn flush(&mut self) -> Option<String> {
+        self.deadline = None;
+        (!self.pending.is_empty()).then(|| std::mem::take(&mut self.pending))
+    }
+}
+
+impl Stream for CoalescedStream {
+    type Item = Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(error) = this.error.take() {
+            return Poll::Ready(Some(Err(error)));
+        }
+
+        while !this.done {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(StreamEvent::ContentBlockDelta { delta, .. }))) => {
+                    let Some(text) = delta["text"].as_str() else {
+                        continue;
+                    };
+                    this.pending.push_str(text);
+                    if this.deadline.is_none() && !this.pending.is_empty() {
+                        this.deadline = Some(Box::pin(time::sleep(this.interval)));
+                    }
+                },
+                Poll::Ready(Some(Ok(_))) => {},
+                Poll::Ready(Some(Err(error))) => match this.flush() {
+                    Some(batch) => {
+                        this.error = Some(error);
+                        return Poll::Ready(Some(Ok(batch)));
+                    },
+                    None => return Poll::Ready(Some(Err(error))),
+                },
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if this.done {
+            return Poll::Ready(this.flush().map(Ok));
+        }
+
+        let is_due = this
+            .deadline
+            .as_mut()
+            .is_some_and(|deadline| deadline.as_mut().poll(cx).is_ready());
+        if is_due {
+            return Poll::Ready(this.flush().map(Ok));
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Abbreviations that do not end a sentence when followed by a period.
+const ABBREVIATIONS: [&str; 16] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "fig", "approx", "inc", "ltd",
+    "co", "dept",
+];
+
+/// [`SentenceStream`] struct emitting the text of a [`MessageStream`] one
+/// sentence at a time, created by [`MessageStream::sentence_chunks`].
+pub struct SentenceStream {
+    /// The underlying stream of events.
+    inner: MessageStream,
+
+    /// Text received that does not form a complete sentence yet.
+    pending: String,
+
+    /// Complete sentences not emitted yet.
+    sentences: VecDeque<String>,
+
+    /// Error to emit once the pending text was flushed.
+    error: Option<AnthropicError>,
+
+    /// Whether the underlying stream ended.
+    done: bool,
+}
+
+impl SentenceStream {
+    /// Get the message assembled so far.
+    #[inline(always)]
+    pub fn message(&self) -> Option<&MessageResponse> {
+        self.inner.message()
+    }
+
+    /// Get the underlying [`MessageStream`], dropping any pending text.
+    #[inline(always)]
+    pub fn into_inner(self) -> MessageStream {
+        self.inner
+    }
+
+    /// Move the complete sentences of the pending text to the queue.
+    fn split_sentences(&mut self) {
+        while let Some(end) = sentence_end(&self.pending) {
+            let sentence = self.pending[..end].trim();
+            if !sentence.is_empty() {
+                self.sentences.push_back(sentence.to_owned());
+            }
+            self.pending.drain(..end);
+        }
+    }
+
+    /// Take the trailing partial sentence, if any.
+    fn flush(&mut self) -> Option<String> {
+        let sentence = self.pending.trim().to_owned();
+        self.pending.clear();
+        (!sentence.is_empty()).then_some(sentence)
+    }
+}
+
+impl Stream for SentenceStream {
+    type Item = Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(sentence) = this.sentences.pop_front() {
+                return Poll::Ready(Some(Ok(sentence)));
+            }
+            if let Some(error) = this.error.take() {
+                return Poll::Ready(Some(Err(error)));
+            }
+            if this.done {
+                return Poll::Ready(this.flush().map(Ok));
+            }
+
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(StreamEvent::ContentBlockDelta { delta, .. }))) => {
+                    if let Some(text) = delta["text"].as_str() {
+                        this.pending.push_str(text);
+                        this.split_sentences();
+                    }
+                },
+                Poll::Ready(Some(Ok(_))) => {},
+                Poll::Ready(Some(Err(error))) => match this.flush() {
+                    Some(sentence) => {
+                        this.error = Some(error);
+                        return Poll::Ready(Some(Ok(sentence)));
+                    },
+                    None => return Poll::Ready(Some(Err(error))),
+                },
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Find the end of the first complete sentence of a text.
+///
+/// A terminator at the very end of the text is not a sentence end yet, since
+/// the text that follows decides, e.g. `3.` followed by `14`.
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, char)) = chars.next() {
+        if !matches!(char, '.' | '!' | '?') {
+            continue;
+        }
+
+        let mut end = index + char.len_utf8();
+        while let Some(&(next_index, next)) = chars.peek() {
+            if !matches!(next, '.' | '!' | '?' | '"' | '\'' | ')' | ']' | '”' | '’') {
+                break;
+            }
+            end = next_index + next.len_utf8();
+            chars.next();
+        }
+
+        match chars.peek() {
+            Some((_, next)) if next.is_whitespace() => {
+                if char != '.' || !is_abbreviation(&text[..index]) {
+                    return Some(end);
+                }
+            },
+            Some(_) => {},
+            None => return None,
+        }
+    }
+
+    None
+}
+
+/// Whether the word ending a text is an abbreviation or an initial.
+fn is_abbreviation(text: &str) -> bool {
+    let word = text
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(|char: char| !char.is_alphanumeric());
+
+    // "I" is far more often a pronoun ending a sentence than an initial.
+    let mut chars = word.chars();
+    let is_initial = chars
+        .next()
+        .is_some_and(|char| char.is_uppercase() && char != 'I')
+        && chars.next().is_none();
+
+    is_initial
+        || word.contains('.')
+        || ABBREVIATIONS
+            .iter()
+            .any(|abbreviation| word.eq_ignore_ascii_case(abbreviation))
+}
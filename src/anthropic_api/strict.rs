@@ -0,0 +1,54 @@
+use serde::{de::Error, Deserialize, Deserializer};
+use std::ops::{Deref, DerefMut};
+
+/// [`Strict`] wrapper rejecting fields its inner type does not model.
+///
+/// Deserializing a `Strict<T>` behaves like `T` with
+/// `#[serde(deny_unknown_fields)]` applied at every level, and fails with the
+/// path of each unexpected field. Types of this crate are lenient by default so
+/// new API fields do not break deserialization; use this wrapper, or
+/// [`AnthropicClient::strict`](super::client::AnthropicClient::strict), during
+/// development to notice fields the types are missing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Strict<T>(pub T);
+
+impl<T> Strict<T> {
+    /// Get the inner value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Strict<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Strict<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Strict<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut unknown = Vec::new();
+        let value =
+            serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string()))?;
+
+        if unknown.is_empty() {
+            Ok(Self(value))
+        } else {
+            Err(D::Error::custom(format!(
+                "unknown fields: {}",
+                unknown.join(", ")
+            )))
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use super::messages::Usage;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::time::Instant;
+
+/// Counter of requests, labeled by `endpoint`, `model`, and `status`.
+///
+/// A request is counted once, however many times it is retried. `status` is
+/// the HTTP status code of the last attempt, `error` for transport failures,
+/// or `cancelled` for requests cancelled by a client shutdown. `endpoint` is
+/// the route of the request, with ids replaced by `{id}`, e.g.
+/// `/messages/batches/{id}/results`.
+pub const REQUESTS_TOTAL: &str = "anthropic_requests_total";
+
+/// Counter of retried attempts, labeled by `endpoint` and `model`.
+pub const REQUEST_RETRIES_TOTAL: &str = "anthropic_request_retries_total";
+
+/// Histogram of request latency in seconds, labeled by `endpoint` and `model`.
+///
+/// The latency covers every attempt of a request, including the delays
+/// between them.
+pub const REQUEST_DURATION_SECONDS: &str = "anthropic_request_duration_seconds";
+
+/// Counter of tokens, labeled by `model` and `type`.
+///
+/// `type` is one of `input`, `output`, `cache_creation_input`, or
+/// `cache_read_input`.
+pub const TOKENS_TOTAL: &str = "anthropic_tokens_total";
+
+/// Path segments naming endpoints rather than resources.
+const ROUTE_SEGMENTS: &[&str] = &[
+    "batches",
+    "cancel",
+    "complete",
+    "content",
+    "count_tokens",
+    "embeddings",
+    "files",
+    "messages",
+    "models",
+    "results",
+];
+
+/// Struct holding the fields of a body that metrics are labeled with.
+#[derive(Deserialize)]
+struct Labels {
+    /// Model of the request.
+    #[serde(default)]
+    model: Option<String>,
+
+    /// Usage of the response.
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// Struct recording the metrics of a single request, across its attempts.
+pub(crate) struct RequestMetrics {
+    /// Route of the endpoint, e.g. `/messages`.
+    endpoint: String,
+
+    /// Model of the request, or `unknown`.
+    model: String,
+
+    /// When the request was started.
+    started: Instant,
+}
+
+impl RequestMetrics {
+    /// Start recording a request to `endpoint` with the serialized `body`.
+    pub(crate) fn start(endpoint: &str, body: &[u8]) -> Self {
+        let model = serde_json::from_slice::<Labels>(body)
+            .ok()
+            .and_then(|labels| labels.model)
+            .unwrap_or_else(|| "unknown".to_owned());
+
+        Self {
+            endpoint: route(endpoint),
+            model,
+            started: Instant::now(),
+        }
+    }
+
+    /// Record that a failed attempt of the request is retried.
+    pub(crate) fn retry(&self) {
+        metrics::counter!(
+            REQUEST_RETRIES_TOTAL,
+            "endpoint" => self.endpoint.clone(),
+            "model" => self.model.clone(),
+        )
+        .increment(1);
+    }
+
+    /// Record the outcome of the last attempt of the request, `None` meaning
+    /// it was cancelled.
+    pub(crate) fn finish(&self, response: Option<Result<(StatusCode, &[u8]), &reqwest::Error>>) {
+        let status = match response {
+            Some(Ok((status, _))) => status.as_str().to_owned(),
+            Some(Err(_)) => "error".to_owned(),
+            None => "cancelled".to_owned(),
+        };
+
+        metrics::counter!(
+            REQUESTS_TOTAL,
+            "endpoint" => self.endpoint.clone(),
+            "model" => self.model.clone(),
+            "status" => status,
+        )
+        .increment(1);
+        metrics::histogram!(
+            REQUEST_DURATION_SECONDS,
+            "endpoint" => self.endpoint.clone(),
+            "model" => self.model.clone(),
+        )
+        .record(self.started.elapsed().as_secs_f64());
+
+        let Some(Ok((status, body))) = response else {
+            return;
+        };
+        let Some(usage) = status
+            .is_success()
+            .then(|| serde_json::from_slice::<Labels>(body).ok())
+            .flatten()
+            .and_then(|labels| labels.usage)
+        else {
+            return;
+        };
+
+        let tokens = [
+            ("input", Some(usage.input_tokens)),
+            ("output", Some(usage.output_tokens)),
+            ("cache_creation_input", usage.cache_creation_input_tokens),
+            ("cache_read_input", usage.cache_read_input_tokens),
+        ];
+        for (kind, count) in tokens {
+            if let Some(count) = count {
+                metrics::counter!(TOKENS_TOTAL, "model" => self.model.clone(), "type" => kind)
+                    .increment(count);
+            }
+        }
+    }
+}
+
+/// Get the route of a path, with its query dropped and ids replaced by `{id}`
+/// so that the endpoint label has a bounded number of values.
+fn route(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+
+    path.split('/')
+        .map(|segment| {
+            if segment.is_empty() || ROUTE_SEGMENTS.contains(&segment) {
+                segment
+            } else {
+                "{id}"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
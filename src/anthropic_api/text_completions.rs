@@ -1,10 +1,10 @@
-use super::client::AnthropicClient;
+use super::{client::AnthropicClient, error::Result, messages::check_stop_sequences};
 use crate::setters;
 use serde::Serialize;
 use serde_json::Value;
 
 /// [`TextCompletionsApi`] struct to interact with the text completions endpoint of the API.
-pub struct TextCompletionsApi<'a, 'b, 'c>(pub(crate) &'a AnthropicClient<'b, 'c>);
+pub struct TextCompletionsApi<'a>(pub(crate) &'a AnthropicClient);
 
 /// Struct representing a request for text completions.
 #[derive(Default, Serialize)]
@@ -112,9 +112,26 @@ impl TextCompletionRequest {
         /// The updated instance of [`TextCompletionRequest`].
         top_k: u64,
     }
+
+    /// Add a sequence at which to stop generating text, keeping those already set.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_sequence` - The sequence, which must contain non-whitespace.
+    ///
+    /// # Returns
+    ///
+    /// The updated instance of [`TextCompletionRequest`].
+    #[inline(always)]
+    pub fn stop_sequence(mut self, stop_sequence: impl Into<String>) -> Self {
+        self.stop_sequences
+            .get_or_insert_with(Vec::new)
+            .push(stop_sequence.into());
+        self
+    }
 }
 
-impl<'a> TextCompletionsApi<'a, '_, '_> {
+impl TextCompletionsApi<'_> {
     /// Create a text completion using the provided request parameters.
     ///
     /// # Arguments
@@ -124,8 +141,11 @@ impl<'a> TextCompletionsApi<'a, '_, '_> {
     /// # Returns
     ///
     /// A [`Result`] containing the JSON response as [`serde_json::Value`] on success,
-    /// or a [`serde_json::Error`] on failure.
-    pub async fn create(&self, request: TextCompletionRequest) -> reqwest::Result<Value> {
+    /// or an [`AnthropicError`](super::error::AnthropicError) on failure, e.g. a
+    /// validation error for a blank stop sequence.
+    pub async fn create(&self, request: TextCompletionRequest) -> Result<Value> {
+        check_stop_sequences(request.stop_sequences.as_deref())?;
+
         // Send a POST request to the text completions endpoint with the request body.
         self.0.post("/complete", &request).await
     }
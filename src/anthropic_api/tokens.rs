@@ -0,0 +1,99 @@
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Average number of characters per token used by the estimates.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated tokens of framing added around each message.
+const TOKENS_PER_MESSAGE: u64 = 4;
+
+/// Estimated tokens of an image, matching a roughly 1.15 megapixel image.
+const TOKENS_PER_IMAGE: u64 = 1_600;
+
+/// Note appended to a text cut by [`truncate_to_tokens`].
+pub const TRUNCATION_MARKER: &str = "\n[truncated]";
+
+/// Estimate the number of tokens of a text.
+///
+/// This is a local heuristic of about four characters per token, meant for
+/// budgeting before a request is sent. It can be off by a fair margin for code
+/// or non-English text; use the token counting endpoint for exact numbers.
+///
+/// # Arguments
+///
+/// * `text` - The text to estimate.
+///
+/// # Returns
+///
+/// The estimated number of tokens.
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Truncate a text to an estimated number of tokens.
+///
+/// Texts within the budget are returned unchanged. Longer texts are cut and
+/// end with [`TRUNCATION_MARKER`], so the model knows the text is incomplete;
+/// the marker counts against the budget. Uses the same heuristic as
+/// [`estimate_tokens`].
+///
+/// # Arguments
+///
+/// * `text` - The text to truncate.
+/// * `max_tokens` - The estimated number of tokens to stay within.
+///
+/// # Returns
+///
+/// The text, truncated if it exceeds the budget.
+pub fn truncate_to_tokens(text: &str, max_tokens: u64) -> Cow<'_, str> {
+    let max_chars = usize::try_from(max_tokens)
+        .unwrap_or(usize::MAX)
+        .saturating_mul(CHARS_PER_TOKEN);
+    let Some((end, _)) = text.char_indices().nth(max_chars) else {
+        return Cow::Borrowed(text);
+    };
+
+    let kept = max_chars.saturating_sub(TRUNCATION_MARKER.chars().count());
+    let end = text
+        .char_indices()
+        .nth(kept)
+        .map_or(end, |(index, _)| index);
+    Cow::Owned(format!("{}{TRUNCATION_MARKER}", &text[..end]))
+}
+
+/// Estimate the number of tokens of a list of messages.
+///
+/// Text anywhere in the messages is estimated with [`estimate_tokens`], image
+/// blocks count as a fixed number of tokens, and each message adds a small
+/// framing overhead.
+///
+/// # Arguments
+///
+/// * `messages` - The messages to estimate, as sent to the API.
+///
+/// # Returns
+///
+/// The estimated number of tokens.
+pub fn estimate_message_tokens(messages: &[Value]) -> u64 {
+    messages
+        .iter()
+        .map(|message| TOKENS_PER_MESSAGE + estimate_value_tokens(&message["content"]))
+        .sum()
+}
+
+/// Estimate the number of tokens of the text and images in a JSON value.
+pub(crate) fn estimate_value_tokens(value: &Value) -> u64 {
+    match value {
+        Value::String(text) => estimate_tokens(text),
+        Value::Array(values) => values.iter().map(estimate_value_tokens).sum(),
+        Value::Object(object) if object.get("type").and_then(Value::as_str) == Some("image") => {
+            TOKENS_PER_IMAGE
+        },
+        Value::Object(object) => object
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "type" | "cache_control"))
+            .map(|(_, value)| estimate_value_tokens(value))
+            .sum(),
+        _ => 0,
+    }
+}
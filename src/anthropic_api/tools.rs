@@ -0,0 +1,69 @@
+use crate::setters;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Struct representing the definition of a tool the model may call.
+///
+/// Convert it into a [`Value`] to pass it to [`MessageRequest::tools`]; the
+/// calls made by the model are read back with
+/// [`MessageResponse::tool_uses`].
+///
+/// [`MessageRequest::tools`]: super::messages::MessageRequest::tools
+/// [`MessageResponse::tool_uses`]: super::messages::MessageResponse::tool_uses
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// Name of the tool, referenced by the calls of the model.
+    pub name: String,
+
+    /// Optional description of what the tool does and when to use it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// JSON schema of the input of the tool.
+    pub input_schema: Value,
+}
+
+impl Tool {
+    /// Create a new instance of [`Tool`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tool, e.g. `get_weather`.
+    /// * `input_schema` - The JSON schema of the input of the tool, an object
+    ///   schema with its `properties`.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of [`Tool`].
+    #[inline(always)]
+    pub fn new(name: impl Into<String>, input_schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            input_schema,
+        }
+    }
+
+    setters! {
+        /// Set the description of the tool.
+        ///
+        /// The model relies on it to decide when to call the tool, so a
+        /// detailed description leads to better calls.
+        ///
+        /// # Arguments
+        ///
+        /// * `description` - What the tool does and when to use it.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`Tool`].
+        description: impl Into<String>,
+    }
+}
+
+impl From<Tool> for Value {
+    #[inline(always)]
+    fn from(tool: Tool) -> Self {
+        serde_json::to_value(tool).unwrap_or_default()
+    }
+}
@@ -0,0 +1,72 @@
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use std::time::Instant;
+use tracing::{field, Span};
+
+/// Name of the span around every request attempt.
+const REQUEST_SPAN: &str = "anthropic_request";
+
+/// Struct holding the model of a body, the only field of it that is traced.
+#[derive(Deserialize)]
+struct Fields {
+    /// Model of the request.
+    #[serde(default)]
+    model: Option<String>,
+}
+
+/// Struct tracing a single attempt of a request.
+///
+/// Only the method, endpoint, model, attempt, status, and latency are
+/// recorded: neither headers, which carry the API key, nor bodies are.
+pub(crate) struct RequestTrace {
+    /// The span of the attempt.
+    span: Span,
+
+    /// When the attempt was started.
+    started: Instant,
+}
+
+impl RequestTrace {
+    /// Start tracing an attempt of a request to `endpoint` with the serialized `body`.
+    pub(crate) fn start(method: &Method, endpoint: &str, body: &[u8], attempt: u32) -> Self {
+        let model = serde_json::from_slice::<Fields>(body)
+            .ok()
+            .and_then(|fields| fields.model);
+        let span = tracing::debug_span!(
+            REQUEST_SPAN,
+            method = %method,
+            endpoint,
+            model = model.as_deref().unwrap_or("unknown"),
+            attempt,
+            status = field::Empty,
+            latency_ms = field::Empty,
+        );
+
+        Self {
+            span,
+            started: Instant::now(),
+        }
+    }
+
+    /// Get the span of the attempt, to instrument the request with.
+    #[inline(always)]
+    pub(crate) fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    /// Record the outcome of the attempt, `None` meaning it was cancelled.
+    pub(crate) fn finish(self, response: Option<Result<StatusCode, &reqwest::Error>>) {
+        let latency_ms = self.started.elapsed().as_millis() as u64;
+        self.span.record("latency_ms", latency_ms);
+
+        let _entered = self.span.enter();
+        match response {
+            Some(Ok(status)) => {
+                self.span.record("status", status.as_u16());
+                tracing::debug!(status = status.as_u16(), latency_ms, "request completed");
+            },
+            Some(Err(error)) => tracing::warn!(%error, latency_ms, "request failed"),
+            None => tracing::debug!(latency_ms, "request cancelled"),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::{future::Future, pin::Pin};
+
+#[cfg(feature = "test-util")]
+pub use mock::{MockRequest, MockTransport};
+
+/// Future returned by an [`HttpTransport`].
+pub type TransportFuture = Pin<Box<dyn Future<Output = reqwest::Result<reqwest::Response>> + Send>>;
+
+/// Trait sending the HTTP requests of an [`AnthropicClient`].
+///
+/// The client builds every request, headers and body included, then hands it
+/// to its transport, which sends it with the `reqwest` client by default. Set
+/// another one with [`AnthropicClient::transport`], e.g. a `MockTransport`
+/// answering with canned responses in tests, behind the `test-util` feature.
+///
+/// [`AnthropicClient`]: super::client::AnthropicClient
+/// [`AnthropicClient::transport`]: super::client::AnthropicClient::transport
+pub trait HttpTransport: Send + Sync {
+    /// Send a request and return its response once the headers are received.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request, ready to be sent.
+    ///
+    /// # Returns
+    ///
+    /// A [`TransportFuture`] resolving to the [`reqwest::Response`].
+    fn execute(&self, request: reqwest::Request) -> TransportFuture;
+}
+
+#[cfg(feature = "test-util")]
+mod mock {
+    use super::{HttpTransport, TransportFuture};
+    use bytes::Bytes;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    /// Struct representing a response scripted on a [`MockTransport`].
+    struct MockResponse {
+        /// HTTP status of the response.
+        status: StatusCode,
+
+        /// Content type of the response.
+        content_type: &'static str,
+
+        /// Body of the response.
+        body: Bytes,
+    }
+
+    /// Struct representing the state shared by the clones of a [`MockTransport`].
+    #[derive(Default)]
+    struct MockState {
+        /// Responses not sent yet, in order.
+        responses: VecDeque<MockResponse>,
+
+        /// Requests received so far, in order.
+        requests: Vec<MockRequest>,
+    }
+
+    /// [`MockTransport`] struct answering requests with scripted responses,
+    /// without any network access.
+    ///
+    /// Responses are sent in the order they were scripted, one per request,
+    /// and every request is recorded. Clones share the same script and
+    /// records, so keep a clone to inspect the requests after passing the
+    /// transport to a client. A request received once the script is exhausted
+    /// panics.
+    #[derive(Clone, Default)]
+    pub struct MockTransport(Arc<Mutex<MockState>>);
+
+    /// Struct representing a request received by a [`MockTransport`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct MockRequest {
+        /// HTTP method of the request.
+        pub method: String,
+
+        /// Full URL of the request.
+        pub url: String,
+
+        /// Headers of the request, with lowercase names.
+        pub headers: Vec<(String, String)>,
+
+        /// Body of the request, empty if it has none.
+        pub body: Bytes,
+    }
+
+    impl MockTransport {
+        /// Create a new instance of [`MockTransport`].
+        ///
+        /// # Returns
+        ///
+        /// A new instance of [`MockTransport`] with no responses scripted.
+        #[inline(always)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Script a JSON response.
+        ///
+        /// # Arguments
+        ///
+        /// * `status` - The HTTP status of the response, e.g. `200` or `529`.
+        /// * `body` - The JSON body of the response.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MockTransport`].
+        pub fn respond(self, status: u16, body: &Value) -> Self {
+            self.push(status, "application/json", body.to_string().into())
+        }
+
+        /// Script a response with a raw body, e.g. a downloaded file.
+        ///
+        /// # Arguments
+        ///
+        /// * `status` - The HTTP status of the response.
+        /// * `content_type` - The content type of the response.
+        /// * `body` - The body of the response.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MockTransport`].
+        pub fn respond_bytes(
+            self,
+            status: u16,
+            content_type: &'static str,
+            body: impl Into<Bytes>,
+        ) -> Self {
+            self.push(status, content_type, body.into())
+        }
+
+        /// Script a streamed response made of server-sent events.
+        ///
+        /// # Arguments
+        ///
+        /// * `events` - The events of the stream, each with its `type`.
+        ///
+        /// # Returns
+        ///
+        /// The updated instance of [`MockTransport`].
+        pub fn respond_stream(self, events: &[Value]) -> Self {
+            let body: String = events
+                .iter()
+                .map(|event| {
+                    let name = event["type"].as_str().unwrap_or_default();
+                    format!("event: {name}\ndata: {event}\n\n")
+                })
+                .collect();
+            self.push(200, "text/event-stream", body.into())
+        }
+
+        /// Get the requests received so far, in order.
+        pub fn requests(&self) -> Vec<MockRequest> {
+            self.0.lock().unwrap().requests.clone()
+        }
+
+        /// Append a response to the script.
+        fn push(self, status: u16, content_type: &'static str, body: Bytes) -> Self {
+            let status = StatusCode::from_u16(status).expect("invalid HTTP status");
+            self.0.lock().unwrap().responses.push_back(MockResponse {
+                status,
+                content_type,
+                body,
+            });
+            self
+        }
+    }
+
+    impl MockRequest {
+        /// Parse the body of the request as JSON.
+        ///
+        /// # Returns
+        ///
+        /// A [`serde_json::Result`] containing the body as a [`Value`].
+        #[inline(always)]
+        pub fn json(&self) -> serde_json::Result<Value> {
+            serde_json::from_slice(&self.body)
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn execute(&self, request: reqwest::Request) -> TransportFuture {
+            let mut state = self.0.lock().unwrap();
+            state.requests.push(MockRequest {
+                method: request.method().as_str().to_owned(),
+                url: request.url().to_string(),
+                headers: request
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                        (name.as_str().to_owned(), value)
+                    })
+                    .collect(),
+                body: request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .map(Bytes::copy_from_slice)
+                    .unwrap_or_default(),
+            });
+
+            let response = state.responses.pop_front();
+            drop(state);
+
+            let Some(response) = response else {
+                panic!(
+                    "MockTransport received {} {} with no response scripted",
+                    request.method(),
+                    request.url()
+                );
+            };
+            let response = http::Response::builder()
+                .status(response.status)
+                .header("content-type", response.content_type)
+                .body(response.body)
+                .expect("invalid mock response");
+
+            Box::pin(async move { Ok(response.into()) })
+        }
+    }
+}
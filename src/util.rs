@@ -1,16 +1,58 @@
+/// Generate the setters of the optional fields of a builder.
+///
+/// A field declared as `field: impl Into<Type>` gets a setter accepting
+/// anything convertible into its type, e.g. `&str` for a `String` field.
 #[macro_export]
 macro_rules! setters {
-    ($(
+    (@setters [$($setter_ident:ident)*]) => {
+        /// Get the names of the optional parameters that are set, e.g. to
+        /// inspect a request without serializing it.
+        ///
+        /// # Returns
+        ///
+        /// The names of the parameters set, as sent to the API, in declaration
+        /// order.
+        pub fn set_fields(&self) -> Vec<&'static str> {
+            let mut fields = Vec::new();
+            $(
+                if self.$setter_ident.is_some() {
+                    fields.push(stringify!($setter_ident));
+                }
+            )*
+            fields
+        }
+    };
+    (
+        @setters [$($done:ident)*]
+        $(#[$setter_attributes:meta])*
+        $setter_ident:ident: impl Into<$setter_type:ty>,
+        $($rest:tt)*
+    ) => {
+        $(#[$setter_attributes])*
+        #[inline(always)]
+        pub fn $setter_ident(mut self, $setter_ident: impl Into<$setter_type>) -> Self {
+            self.$setter_ident = Some($setter_ident.into());
+            self
+        }
+
+        $crate::setters!(@setters [$($done)* $setter_ident] $($rest)*);
+    };
+    (
+        @setters [$($done:ident)*]
         $(#[$setter_attributes:meta])*
         $setter_ident:ident: $setter_type:ty,
-    )*) => {
-        $(
-            $(#[$setter_attributes])*
-            #[inline(always)]
-            pub fn $setter_ident(mut self, $setter_ident: $setter_type) -> Self {
-                self.$setter_ident = Some($setter_ident);
-                self
-            }
-        )*
+        $($rest:tt)*
+    ) => {
+        $(#[$setter_attributes])*
+        #[inline(always)]
+        pub fn $setter_ident(mut self, $setter_ident: $setter_type) -> Self {
+            self.$setter_ident = Some($setter_ident);
+            self
+        }
+
+        $crate::setters!(@setters [$($done)* $setter_ident] $($rest)*);
+    };
+    ($($setters:tt)*) => {
+        $crate::setters!(@setters [] $($setters)*);
     };
 }
@@ -0,0 +1,95 @@
+#![cfg(feature = "test-util")]
+
+use futures_core::Stream;
+use rusty_anthropic::anthropic_api::{
+    batches::{BatchResult, ProcessingStatus},
+    client::AnthropicClient,
+    page::ListParams,
+    transport::MockTransport,
+};
+use serde_json::{json, Value};
+use std::{future::poll_fn, pin::Pin};
+
+fn batch(id: &str, status: &str) -> Value {
+    json!({"type":"message_batch","id":id,"processing_status":status,"request_counts":{"processing":0,"succeeded":2,"errored":1,"canceled":0,"expired":0},"created_at":"2025-04-14T12:00:00Z","expires_at":"2025-04-15T12:00:00Z","ended_at":"2025-04-14T12:30:00Z","cancel_initiated_at":null,"archived_at":null,"results_url":format!("https://api.anthropic.com/v1/messages/batches/{id}/results")})
+}
+
+const RESULTS: &str = concat!(
+    r#"{"custom_id":"paris","result":{"type":"succeeded","message":{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"15°C"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":12,"output_tokens":4}}}}"#,
+    "\n",
+    r#"{"custom_id":"london","result":{"type":"expired"}}"#,
+    "\n",
+);
+
+#[tokio::test]
+async fn batches_are_listed_canceled_fetched_and_deleted() {
+    let transport = MockTransport::new()
+        .respond(
+            200,
+            &json!({"data":[batch("msgbatch_01", "ended")],"has_more":true,"first_id":"msgbatch_01","last_id":"msgbatch_01"}),
+        )
+        .respond(
+            200,
+            &json!({"data":[],"has_more":false,"first_id":null,"last_id":null}),
+        )
+        .respond(200, &batch("msgbatch_02", "canceling"))
+        .respond_bytes(200, "application/binary", RESULTS)
+        .respond(
+            200,
+            &json!({"type":"message_batch_deleted","id":"msgbatch_04"}),
+        );
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+    let batches = client.batches();
+
+    let page = batches.list(&ListParams::new()).await.unwrap();
+    assert!(page.has_more);
+    assert_eq!(page.data[0].processing_status, ProcessingStatus::Ended);
+    assert_eq!(page.data[0].request_counts.succeeded, 2);
+    assert_eq!(
+        page.data[0].ended_at.as_deref(),
+        Some("2025-04-14T12:30:00Z")
+    );
+    let params = ListParams::new()
+        .limit(50)
+        .after_id("msgbatch_01".to_owned());
+    assert!(batches.list(&params).await.unwrap().data.is_empty());
+
+    let canceled = batches.cancel("msgbatch_02").await.unwrap();
+    assert_eq!(canceled.processing_status, ProcessingStatus::Canceling);
+
+    let mut results = batches.results("msgbatch_03").await.unwrap();
+    let mut lines = Vec::new();
+    while let Some(line) = poll_fn(|cx| Pin::new(&mut results).poll_next(cx)).await {
+        lines.push(line.unwrap());
+    }
+    assert_eq!(lines.len(), 2);
+    assert!(
+        matches!(&lines[0].result, BatchResult::Succeeded { message } if message.id == "msg_01")
+    );
+    assert!(matches!(lines[1].result, BatchResult::Expired));
+
+    assert_eq!(
+        batches.delete("msgbatch_04").await.unwrap().id,
+        "msgbatch_04"
+    );
+
+    let sent: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|request| (request.method.clone(), request.url.clone()))
+        .collect();
+    assert_eq!(
+        sent,
+        [
+            ("GET", "http://mock/v1/messages/batches"),
+            (
+                "GET",
+                "http://mock/v1/messages/batches?limit=50&after_id=msgbatch_01"
+            ),
+            ("POST", "http://mock/v1/messages/batches/msgbatch_02/cancel"),
+            ("GET", "http://mock/v1/messages/batches/msgbatch_03/results"),
+            ("DELETE", "http://mock/v1/messages/batches/msgbatch_04"),
+        ]
+        .map(|(method, url)| (method.to_owned(), url.to_owned()))
+    );
+}
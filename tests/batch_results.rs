@@ -0,0 +1,67 @@
+use bytes::Bytes;
+use futures_core::Stream;
+use rusty_anthropic::anthropic_api::batches::{BatchResult, BatchResultLine, BatchResults};
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Results file of a batch with a success, a failure, and an expired request.
+const RESULTS: &str = r#"{"custom_id":"paris","result":{"type":"succeeded","message":{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"15°C"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":12,"output_tokens":4}}}}
+{"custom_id":"tokyo","result":{"type":"errored","error":{"type":"error","error":{"type":"invalid_request_error","message":"max_tokens: Field required"}}}}
+
+{"custom_id":"london","result":{"type":"expired"}}
+"#;
+
+/// Body delivered in fixed-size chunks, splitting lines and characters.
+struct Chunks(VecDeque<reqwest::Result<Bytes>>);
+
+impl Stream for Chunks {
+    type Item = reqwest::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().0.pop_front())
+    }
+}
+
+fn check(lines: &[BatchResultLine]) {
+    let ids: Vec<&str> = lines.iter().map(|line| line.custom_id.as_str()).collect();
+    assert_eq!(ids, ["paris", "tokyo", "london"]);
+    assert!(
+        matches!(&lines[0].result, BatchResult::Succeeded { message } if message.id == "msg_01")
+    );
+    assert!(matches!(&lines[1].result, BatchResult::Errored { error }
+        if error["error"]["type"] == "invalid_request_error"));
+    assert!(matches!(lines[2].result, BatchResult::Expired));
+}
+
+#[tokio::test]
+async fn results_split_across_chunks_are_parsed_per_line() {
+    // No trailing newline on the last line, and chunks cutting "°" in two.
+    let body = RESULTS.trim_end().as_bytes();
+    let chunks = body
+        .chunks(7)
+        .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+        .collect();
+    let mut results = BatchResults::new(Chunks(chunks));
+
+    let mut lines = Vec::new();
+    while let Some(line) = poll_fn(|cx| Pin::new(&mut results).poll_next(cx)).await {
+        lines.push(line.unwrap());
+    }
+    check(&lines);
+}
+
+#[test]
+fn malformed_lines_fail_without_stopping_the_file() {
+    let file = format!("{{\"custom_id\": 42}}\n{RESULTS}");
+
+    let lines: Vec<_> = BatchResultLine::parse_lines(file.as_bytes()).collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].is_err());
+
+    let lines: Vec<_> = lines.into_iter().skip(1).map(Result::unwrap).collect();
+    check(&lines);
+}
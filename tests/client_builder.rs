@@ -0,0 +1,23 @@
+use rusty_anthropic::anthropic_api::{client::AnthropicClient, error::AnthropicError};
+use std::time::Duration;
+
+#[test]
+fn builder_configures_or_takes_the_http_client() {
+    let client = AnthropicClient::builder("http://localhost", "key")
+        .timeout(Duration::from_secs(60))
+        .connect_timeout(Duration::from_secs(5))
+        .pool_max_idle_per_host(4)
+        .build();
+    assert!(client.is_ok());
+
+    let client = AnthropicClient::builder("http://localhost", "key")
+        .client(reqwest::Client::new())
+        .build();
+    assert!(client.is_ok());
+
+    let client = AnthropicClient::builder("http://localhost", "key")
+        .client(reqwest::Client::new())
+        .timeout(Duration::from_secs(60))
+        .build();
+    assert!(matches!(client, Err(AnthropicError::Validation(_))));
+}
@@ -0,0 +1,27 @@
+use rusty_anthropic::anthropic_api::{client::AnthropicClient, error::AnthropicError};
+use std::env;
+
+// A single test, since the variables are shared by the whole process.
+#[test]
+fn from_env_requires_the_api_key() {
+    env::remove_var("ANTHROPIC_API_KEY");
+    env::remove_var("ANTHROPIC_BASE_URL");
+    let error = AnthropicClient::from_env().err().unwrap();
+    assert!(matches!(
+        error,
+        AnthropicError::EnvVar {
+            name: "ANTHROPIC_API_KEY",
+            ..
+        }
+    ));
+    assert!(error.to_string().contains("ANTHROPIC_API_KEY"));
+
+    env::set_var("ANTHROPIC_API_KEY", "");
+    assert!(AnthropicClient::from_env().is_err());
+
+    env::set_var("ANTHROPIC_API_KEY", "sk-ant-test");
+    assert!(AnthropicClient::from_env().is_ok());
+
+    env::set_var("ANTHROPIC_BASE_URL", "http://localhost:8080/v1");
+    assert!(AnthropicClient::from_env().is_ok());
+}
@@ -0,0 +1,108 @@
+use rusty_anthropic::anthropic_api::{
+    content::{ContentBlock, ToolUse},
+    messages::MessageResponse,
+    streaming::{MessageAccumulator, StreamEvent},
+};
+use serde_json::{json, Value};
+
+fn response(content: Value) -> MessageResponse {
+    serde_json::from_value(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": content,
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 8, "output_tokens": 3 },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn response_blocks_are_typed_and_unknown_blocks_kept() {
+    let search = json!({
+        "type": "server_tool_use",
+        "id": "srvtoolu_01",
+        "name": "web_search",
+        "input": { "query": "weather in Paris" },
+    });
+    let content = json!([
+        { "type": "thinking", "thinking": "Search first.", "signature": "c2ln" },
+        { "type": "tool_use", "id": "toolu_01", "name": "get_time", "input": {} },
+        search,
+        { "type": "text", "text": "Sunny." },
+    ]);
+    let response = response(content.clone());
+
+    assert_eq!(
+        response.content,
+        [
+            ContentBlock::Thinking {
+                thinking: "Search first.".into(),
+                signature: "c2ln".into(),
+            },
+            ContentBlock::ToolUse(ToolUse {
+                id: "toolu_01".into(),
+                name: "get_time".into(),
+                input: json!({}),
+            }),
+            ContentBlock::Unknown(search),
+            ContentBlock::text("Sunny."),
+        ]
+    );
+    assert_eq!(serde_json::to_value(&response.content).unwrap(), content);
+}
+
+#[test]
+fn streamed_blocks_of_unknown_type_are_assembled() {
+    let mut accumulator = MessageAccumulator::new();
+    let mut message = response(json!([]));
+    message.stop_reason = None;
+
+    let events = [
+        StreamEvent::MessageStart { message },
+        StreamEvent::ContentBlockStart {
+            index: 0,
+            content_block: json!({ "type": "server_tool_use", "id": "srvtoolu_01", "name": "web_search", "input": {} }),
+        },
+        StreamEvent::ContentBlockDelta {
+            index: 0,
+            delta: json!({ "type": "input_json_delta", "partial_json": "{\"query\": \"Paris\"}" }),
+        },
+        StreamEvent::ContentBlockStop { index: 0 },
+        StreamEvent::ContentBlockStart {
+            index: 1,
+            content_block: json!({ "type": "thinking", "thinking": "" }),
+        },
+        StreamEvent::ContentBlockDelta {
+            index: 1,
+            delta: json!({ "type": "thinking_delta", "thinking": "Done." }),
+        },
+        StreamEvent::ContentBlockDelta {
+            index: 1,
+            delta: json!({ "type": "signature_delta", "signature": "c2ln" }),
+        },
+        StreamEvent::ContentBlockStop { index: 1 },
+    ];
+    for event in &events {
+        accumulator.push(event);
+    }
+
+    let message = accumulator.finish().unwrap();
+    assert_eq!(
+        message.content,
+        [
+            ContentBlock::Unknown(json!({
+                "type": "server_tool_use",
+                "id": "srvtoolu_01",
+                "name": "web_search",
+                "input": { "query": "Paris" },
+            })),
+            ContentBlock::Thinking {
+                thinking: "Done.".into(),
+                signature: "c2ln".into(),
+            },
+        ]
+    );
+}
@@ -0,0 +1,55 @@
+use rusty_anthropic::anthropic_api::{
+    content::Message, messages::MessageRequest, model::Model, tokens::estimate_message_tokens,
+};
+use serde_json::json;
+
+fn request() -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(1_024)
+}
+
+#[test]
+fn system_prompt_and_tools_count_towards_the_input() {
+    let model = Model::from_id("claude-sonnet-4-5");
+    let messages_only = request().fits_context(&model).unwrap();
+    assert_eq!(
+        messages_only.estimated_input_tokens,
+        estimate_message_tokens(&[Message::user("Hi").into()])
+    );
+
+    let system = "a".repeat(4_000);
+    let with_system = request()
+        .system(system.into())
+        .fits_context(&model)
+        .unwrap();
+    assert_eq!(
+        with_system.estimated_input_tokens,
+        messages_only.estimated_input_tokens + 1_000
+    );
+
+    let with_tools = request()
+        .tools(vec![json!({
+            "name": "get_weather",
+            "description": "Get the current weather in a city.",
+            "input_schema": { "type": "object", "properties": { "city": { "type": "string" } } },
+        })])
+        .fits_context(&model)
+        .unwrap();
+    assert!(with_tools.estimated_input_tokens > messages_only.estimated_input_tokens);
+}
+
+#[test]
+fn a_large_system_prompt_lowers_the_output_limit() {
+    let model = Model::from_id("claude-sonnet-4-5");
+    let context_window = model.context_window().unwrap();
+
+    let system = "a".repeat(context_window as usize * 4 - 8_000);
+    let request = request()
+        .system(system.into())
+        .max_tokens_to_model_limit()
+        .unwrap();
+    let fit = request.fits_context(&model).unwrap();
+    assert!(fit.fits);
+    assert_eq!(fit.headroom, 0);
+    assert!(fit.max_output_tokens < 2_000);
+}
@@ -0,0 +1,127 @@
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::{ContentBlock, Message},
+    conversation::Conversation,
+    error::AnthropicError,
+    messages::StopReason,
+};
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Answer a single request with `body`, returning the base URL to send it to.
+async fn serve_once(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let _ = socket.read(&mut buffer).await.unwrap();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+             connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    base_url
+}
+
+#[tokio::test]
+async fn send_returns_the_typed_reply_and_records_it() {
+    let base_url = serve_once(
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}}"#,
+    )
+    .await;
+    let client = AnthropicClient::new(base_url, "key");
+
+    let mut conversation = Conversation::new("claude-sonnet-4-5".into(), 256);
+    conversation.user("Hi");
+    let response = conversation.send(&client).await.unwrap();
+
+    assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+    assert_eq!(response.usage.output_tokens, 3);
+    assert_eq!(
+        conversation.messages()[1],
+        json!({ "role": "assistant", "content": [{ "type": "text", "text": "Hello!" }] })
+    );
+}
+
+#[tokio::test]
+async fn replies_that_fail_to_deserialize_are_not_recorded() {
+    let base_url = serve_once(
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3},"surprise":true}"#,
+    )
+    .await;
+    let client = AnthropicClient::new(base_url, "key").strict(true);
+
+    let mut conversation = Conversation::new("claude-sonnet-4-5".into(), 256);
+    conversation.user("Hi");
+    assert!(conversation.send(&client).await.is_err());
+
+    assert_eq!(
+        conversation.messages(),
+        [json!({ "role": "user", "content": "Hi" })]
+    );
+}
+
+#[tokio::test]
+async fn replies_extend_a_prefill_but_not_a_complete_reply() {
+    let base_url = serve_once(
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":" Paris."}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}}"#,
+    )
+    .await;
+    let client = AnthropicClient::new(base_url, "key");
+
+    let mut conversation = Conversation::new("claude-sonnet-4-5".into(), 256);
+    conversation
+        .user("What is the capital of France?")
+        .push(Message::assistant("The capital is"));
+    conversation.send(&client).await.unwrap();
+
+    assert_eq!(conversation.messages().len(), 2);
+    assert_eq!(
+        conversation.messages()[1]["content"],
+        json!([
+            { "type": "text", "text": "The capital is" },
+            { "type": "text", "text": " Paris." },
+        ])
+    );
+
+    let error = conversation.send(&client).await.unwrap_err();
+    assert!(matches!(error, AnthropicError::Validation(_)));
+    assert_eq!(conversation.messages().len(), 2);
+}
+
+#[tokio::test]
+async fn prefilled_replies_keep_their_tool_calls() {
+    let base_url = serve_once(
+        r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":" check the weather."},{"type":"tool_use","id":"toolu_01","name":"get_weather","input":{"city":"Paris"}}],"stop_reason":"tool_use","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":20}}"#,
+    )
+    .await;
+    let client = AnthropicClient::new(base_url, "key");
+
+    let mut conversation = Conversation::new("claude-sonnet-4-5".into(), 256);
+    conversation.user("Weather in Paris?");
+    let text = conversation
+        .send_with_prefill(&client, "Let me")
+        .await
+        .unwrap();
+
+    assert_eq!(text, "Let me check the weather.");
+    assert_eq!(
+        conversation.messages()[1]["content"],
+        json!([
+            { "type": "text", "text": "Let me check the weather." },
+            { "type": "tool_use", "id": "toolu_01", "name": "get_weather", "input": { "city": "Paris" } },
+        ])
+    );
+    conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_01", "15°C"))
+        .unwrap();
+}
@@ -0,0 +1,123 @@
+use rusty_anthropic::anthropic_api::{
+    content::{ContentBlock, Message, Role},
+    conversation::Conversation,
+};
+use serde_json::json;
+
+/// Build a conversation where the model made two tool calls at once.
+fn conversation_with_two_tool_calls() -> Conversation {
+    let mut conversation = Conversation::new("claude-sonnet-4-5".into(), 1024);
+    conversation.user("What is the weather in Paris and in Tokyo?");
+    conversation.push(Message {
+        role: Role::Assistant,
+        content: vec![
+            json!({ "type": "text", "text": "Let me check both cities." }),
+            json!({
+                "type": "tool_use",
+                "id": "toolu_paris",
+                "name": "get_weather",
+                "input": { "city": "Paris" },
+            }),
+            json!({
+                "type": "tool_use",
+                "id": "toolu_tokyo",
+                "name": "get_weather",
+                "input": { "city": "Tokyo" },
+            }),
+        ],
+    });
+
+    conversation
+}
+
+#[test]
+fn concurrent_tool_results_are_correlated_by_id() {
+    let mut conversation = conversation_with_two_tool_calls();
+
+    let pending = conversation.pending_tool_uses();
+    let ids: Vec<&str> = pending
+        .iter()
+        .map(|tool_use| tool_use.id.as_str())
+        .collect();
+    assert_eq!(ids, ["toolu_paris", "toolu_tokyo"]);
+    assert_eq!(pending[1].name, "get_weather");
+    assert_eq!(pending[1].input, json!({ "city": "Tokyo" }));
+
+    // Results may come back in any order.
+    conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_tokyo", "22°C, sunny"))
+        .unwrap();
+    assert_eq!(conversation.pending_tool_uses()[0].id, "toolu_paris");
+
+    conversation
+        .add_tool_result(ContentBlock::tool_result_error(
+            "toolu_paris",
+            "service unavailable",
+        ))
+        .unwrap();
+    assert!(conversation.pending_tool_uses().is_empty());
+
+    let messages = conversation.messages();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[2],
+        json!({
+            "role": "user",
+            "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_tokyo", "content": "22°C, sunny" },
+                {
+                    "type": "tool_result",
+                    "tool_use_id": "toolu_paris",
+                    "content": "service unavailable",
+                    "is_error": true,
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn tool_results_with_unknown_or_answered_ids_are_rejected() {
+    let mut conversation = conversation_with_two_tool_calls();
+
+    assert!(conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_london", "18°C"))
+        .is_err());
+
+    conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_paris", "15°C"))
+        .unwrap();
+    assert!(conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_paris", "15°C"))
+        .is_err());
+    assert!(conversation
+        .add_tool_result(ContentBlock::text("not a tool result"))
+        .is_err());
+}
+
+#[test]
+fn last_turns_keep_tool_results_with_their_calls() {
+    let mut conversation = conversation_with_two_tool_calls().system("Be brief.");
+    conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_paris", "15°C"))
+        .unwrap();
+    conversation
+        .add_tool_result(ContentBlock::tool_result("toolu_tokyo", "22°C"))
+        .unwrap();
+    conversation.push(Message::assistant("15°C in Paris, 22°C in Tokyo."));
+    conversation.user("And in London?");
+
+    let request = serde_json::to_value(conversation.request_for_last_n(1)).unwrap();
+    assert_eq!(request["system"], "Be brief.");
+    assert_eq!(request["messages"].as_array().unwrap().len(), 1);
+
+    let request = serde_json::to_value(conversation.request_for_last_n(2)).unwrap();
+    let messages = request["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 5);
+    assert_eq!(
+        messages[0]["content"],
+        "What is the weather in Paris and in Tokyo?"
+    );
+
+    assert_eq!(conversation.messages().len(), 5);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::Message,
+    messages::{MessageRequest, ThinkingConfig, TokenCount},
+    transport::MockTransport,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn only_the_inputs_of_the_request_are_counted() {
+    let transport = MockTransport::new().respond(200, &json!({ "input_tokens": 2_095 }));
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .system("Be brief.".into())
+        .tools(vec![
+            json!({ "name": "search", "input_schema": { "type": "object" } }),
+        ])
+        .thinking(ThinkingConfig::enabled(1_024))
+        .max_tokens(2_048)
+        .temperature(0.2);
+    let count = client.messages().count_tokens(&request).await.unwrap();
+    assert_eq!(
+        count,
+        TokenCount {
+            input_tokens: 2_095
+        }
+    );
+
+    let sent = transport.requests().pop().unwrap();
+    assert_eq!(sent.url, "http://mock/v1/messages/count_tokens");
+
+    let body = sent.json().unwrap();
+    assert_eq!(body["model"], "claude-sonnet-4-5");
+    assert_eq!(body["system"], "Be brief.");
+    assert_eq!(body["tools"][0]["name"], "search");
+    assert_eq!(
+        body["thinking"],
+        json!({ "type": "enabled", "budget_tokens": 1_024 })
+    );
+    assert!(body.get("max_tokens").is_none());
+    assert!(body.get("temperature").is_none());
+}
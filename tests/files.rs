@@ -0,0 +1,120 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    error::AnthropicError,
+    files::FILES_BETA,
+    page::ListParams,
+    transport::{MockRequest, MockTransport},
+};
+use serde_json::{json, Value};
+
+fn metadata(id: &str) -> Value {
+    json!({"type":"file","id":id,"filename":"report.txt","mime_type":"text/plain","size_bytes":17,"created_at":"2025-04-14T12:00:00Z","downloadable":false})
+}
+
+fn header<'a>(request: &'a MockRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[tokio::test]
+async fn uploads_are_sent_as_multipart_form_data() {
+    let transport = MockTransport::new().respond(200, &metadata("file_01"));
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let file = client
+        .files()
+        .upload("report \"final\".txt", b"Quarterly numbers", "text/plain")
+        .await
+        .unwrap();
+    assert_eq!(file.id, "file_01");
+    assert_eq!(file.mime_type, "text/plain");
+    assert_eq!(file.size_bytes, 17);
+    assert!(!file.downloadable);
+
+    let request = transport.requests().pop().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.url, "http://mock/v1/files");
+    assert_eq!(header(&request, "anthropic-beta"), Some(FILES_BETA));
+
+    let content_type = header(&request, "content-type").unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(request.body.to_vec()).unwrap(),
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; \
+             filename=\"report %22final%22.txt\"\r\nContent-Type: text/plain\r\n\r\n\
+             Quarterly numbers\r\n--{boundary}--\r\n"
+        )
+    );
+}
+
+#[tokio::test]
+async fn files_are_listed_retrieved_deleted_and_downloaded() {
+    let transport = MockTransport::new()
+        .respond(
+            200,
+            &json!({"data":[metadata("file_01")],"has_more":false,"first_id":"file_01","last_id":"file_01"}),
+        )
+        .respond(200, &metadata("file_01"))
+        .respond(200, &json!({"type":"file_deleted","id":"file_02"}))
+        .respond_bytes(200, "application/octet-stream", "Quarterly numbers");
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+    let files = client.files();
+
+    let page = files.list(&ListParams::new().limit(10)).await.unwrap();
+    assert!(!page.has_more);
+    assert_eq!(page.data[0].filename, "report.txt");
+    let file = files.retrieve("file_01").await.unwrap();
+    assert_eq!(file.created_at, "2025-04-14T12:00:00Z");
+    assert_eq!(files.delete("file_02").await.unwrap().id, "file_02");
+    assert_eq!(
+        files.download("file_03").await.unwrap(),
+        "Quarterly numbers"
+    );
+
+    let requests = transport.requests();
+    assert!(requests
+        .iter()
+        .all(|request| header(request, "anthropic-beta") == Some(FILES_BETA)));
+    let sent: Vec<_> = requests
+        .iter()
+        .map(|request| (request.method.as_str(), request.url.as_str()))
+        .collect();
+    assert_eq!(
+        sent,
+        [
+            ("GET", "http://mock/v1/files?limit=10"),
+            ("GET", "http://mock/v1/files/file_01"),
+            ("DELETE", "http://mock/v1/files/file_02"),
+            ("GET", "http://mock/v1/files/file_03/content"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn uploads_reject_media_types_that_would_break_the_part_headers() {
+    let transport = MockTransport::new();
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    for media_type in [
+        "text/plain\r\nX-Evil: 1",
+        "text/plain\nX-Evil: 1",
+        "plain",
+        "text/",
+    ] {
+        let error = client
+            .files()
+            .upload("report.txt", b"Quarterly numbers", media_type)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AnthropicError::Validation(_)), "{error}");
+    }
+    assert!(transport.requests().is_empty());
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::Message,
+    messages::MessageRequest,
+    transport::{MockRequest, MockTransport},
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn request() -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()]).max_tokens(64)
+}
+
+fn overloaded() -> Value {
+    json!({"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}})
+}
+
+fn reply() -> Value {
+    json!({"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}})
+}
+
+/// Create a client sending its requests through `transport`, retrying once.
+fn client(transport: &MockTransport) -> AnthropicClient {
+    AnthropicClient::builder("http://mock/v1", "key")
+        .max_retries(1)
+        .base_delay(Duration::from_millis(1))
+        .build()
+        .unwrap()
+        .transport(transport.clone())
+}
+
+fn key(request: &MockRequest) -> &str {
+    request
+        .headers
+        .iter()
+        .find(|(name, _)| name == "idempotency-key")
+        .map(|(_, value)| value.as_str())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn retries_carry_the_key_of_the_call() {
+    let transport = MockTransport::new()
+        .respond(529, &overloaded())
+        .respond(200, &reply())
+        .respond(529, &overloaded())
+        .respond(200, &reply());
+    let client = client(&transport);
+
+    client
+        .messages()
+        .create_with_idempotency_key(request(), "order-42")
+        .await
+        .unwrap();
+    client.messages().create(request()).await.unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(key(&requests[0]), "order-42");
+    assert_eq!(key(&requests[1]), "order-42");
+
+    let generated = key(&requests[2]);
+    assert_eq!(key(&requests[3]), generated);
+    assert_ne!(generated, "order-42");
+    assert_eq!(generated.len(), 36);
+    assert_eq!(&generated[14..15], "4");
+}
+
+#[tokio::test]
+async fn separate_calls_get_separate_keys() {
+    let transport = MockTransport::new()
+        .respond(200, &reply())
+        .respond(200, &reply());
+    let client = client(&transport);
+
+    client.messages().create(request()).await.unwrap();
+    client.messages().create(request()).await.unwrap();
+
+    let requests = transport.requests();
+    assert_ne!(key(&requests[0]), key(&requests[1]));
+}
@@ -0,0 +1,46 @@
+use rusty_anthropic::anthropic_api::{
+    content::{ImageOrderWarning, ImageSource, Message},
+    messages::MessageRequest,
+};
+use serde_json::{json, Value};
+
+fn image() -> Value {
+    json!({ "type": "image", "source": { "type": "url", "url": "https://example.com/a.png" } })
+}
+
+#[test]
+fn text_before_images_is_reported_and_reordered() {
+    let request = MessageRequest::new(
+        "claude-3-5-sonnet-20240620".into(),
+        vec![
+            json!({ "role": "user", "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "ok" },
+                { "type": "text", "text": "What is in this image?" },
+                image(),
+            ]}),
+            json!({ "role": "assistant", "content": [{ "type": "text", "text": "A cat." }, image()] }),
+            Message::user_with_labeled_images("Compare them.", vec![ImageSource::url("a"); 2])
+                .into(),
+        ],
+    );
+
+    assert_eq!(
+        request.image_order_warnings(),
+        [ImageOrderWarning {
+            message: 0,
+            text_block: 1,
+            image_block: 2,
+        }]
+    );
+
+    let request = serde_json::to_value(request.images_first()).unwrap();
+    let types: Vec<&str> = request["messages"][0]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|block| block["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["tool_result", "image", "text"]);
+    assert_eq!(request["messages"][1]["content"][0]["type"], "text");
+    assert_eq!(request["messages"][2]["content"][0]["text"], "Image 1:");
+}
@@ -0,0 +1,18 @@
+use rusty_anthropic::anthropic_api::embeddings::EmbeddingsRequest;
+
+#[test]
+fn string_setters_accept_str_and_string() {
+    let inputs = vec!["The quick brown fox".to_owned()];
+
+    let borrowed = EmbeddingsRequest::new("voyage-3".into(), inputs.clone())
+        .input_type("document")
+        .encoding_format("base64");
+    let owned = EmbeddingsRequest::new("voyage-3".into(), inputs)
+        .input_type(String::from("document"))
+        .encoding_format(String::from("base64"));
+
+    let borrowed = serde_json::to_value(borrowed).unwrap();
+    assert_eq!(borrowed["input_type"], "document");
+    assert_eq!(borrowed["encoding_format"], "base64");
+    assert_eq!(borrowed, serde_json::to_value(owned).unwrap());
+}
@@ -0,0 +1,40 @@
+use rusty_anthropic::anthropic_api::{
+    content::ContentBlock, lazy::LazyMessageResponse, messages::StopReason,
+};
+use serde_json::{json, Value};
+
+#[test]
+fn blocks_are_parsed_on_demand() {
+    let body = json!({
+        "id": "msg_1",
+        "type": "message",
+        "model": "claude-sonnet-4-5",
+        "role": "assistant",
+        "content": [
+            { "type": "text", "text": "Running the script.\n" },
+            { "type": "tool_use", "id": "toolu_1", "name": "run", "input": { "script": "ls" } },
+            { "type": "tool_result", "tool_use_id": "toolu_1", "content": "x".repeat(100_000) },
+            { "type": "text", "text": "Done." },
+        ],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 10, "output_tokens": 20 },
+    });
+    let response = LazyMessageResponse::new(serde_json::to_vec(&body).unwrap());
+
+    assert_eq!(response.text().unwrap(), "Running the script.\nDone.");
+    assert_eq!(response.block_count().unwrap(), 4);
+    assert_eq!(response.stop_reason().unwrap(), Some(StopReason::EndTurn));
+    assert_eq!(response.usage().unwrap().output_tokens, 20);
+    assert_eq!(
+        response.field::<String>("id").unwrap().as_deref(),
+        Some("msg_1")
+    );
+    assert_eq!(response.tool_uses().unwrap()[0].input["script"], "ls");
+    assert_eq!(
+        response.block::<ContentBlock>(3).unwrap(),
+        Some(ContentBlock::text("Done."))
+    );
+    assert!(response.block::<Value>(4).unwrap().is_none());
+    assert_eq!(response.parse().unwrap().content.len(), 4);
+}
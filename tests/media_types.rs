@@ -0,0 +1,33 @@
+use rusty_anthropic::anthropic_api::content::{normalize_media_type, ContentBlock, ImageSource};
+
+/// Base64 of the first bytes of a JPEG file.
+const JPEG: &str = "/9j/4AAQSkZJRgABAQ==";
+
+#[test]
+fn aliases_are_mapped_to_canonical_media_types() {
+    assert_eq!(normalize_media_type("image/jpg").unwrap(), "image/jpeg");
+    assert_eq!(normalize_media_type(" Image/PNG ").unwrap(), "image/png");
+    assert_eq!(
+        normalize_media_type("image/webp; charset=binary").unwrap(),
+        "image/webp"
+    );
+
+    assert_eq!(
+        ContentBlock::image_base64("image/JPG", JPEG).unwrap(),
+        ContentBlock::image(ImageSource::base64("image/jpeg", JPEG).unwrap())
+    );
+    assert_eq!(
+        ImageSource::base64("image/jpg", JPEG).unwrap(),
+        ImageSource::base64("image/jpeg", JPEG).unwrap()
+    );
+}
+
+#[test]
+fn unsupported_media_types_list_the_supported_ones() {
+    let error = normalize_media_type("image/tiff").unwrap_err().to_string();
+
+    assert!(error.contains("image/tiff"));
+    assert!(error.contains("image/jpeg, image/png, image/gif, image/webp"));
+    assert!(ContentBlock::image_base64("image/bmp", JPEG).is_err());
+    assert!(ImageSource::base64("image/tiff", JPEG).is_err());
+}
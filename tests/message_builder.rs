@@ -0,0 +1,49 @@
+use rusty_anthropic::anthropic_api::{
+    content::{ContentBlock, ImageSource, Message, MessageBuilder, Role},
+    error::AnthropicError,
+};
+use serde_json::{json, Value};
+
+/// First bytes of a JPEG file.
+const JPEG_BYTES: [u8; 13] = [
+    0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01,
+];
+
+#[test]
+fn builder_encodes_images_and_keeps_block_order() {
+    let message = MessageBuilder::user()
+        .image_bytes("image/jpg", JPEG_BYTES)
+        .image_url("https://example.com/cat.png")
+        .text("Compare these images.")
+        .build()
+        .unwrap();
+
+    assert_eq!(message.role, Role::User);
+    assert_eq!(
+        message.content,
+        [
+            Value::from(ContentBlock::image(
+                ImageSource::base64("image/jpeg", "/9j/4AAQSkZJRgABAQ==").unwrap()
+            )),
+            json!({
+                "type": "image",
+                "source": { "type": "url", "url": "https://example.com/cat.png" },
+            }),
+            json!({ "type": "text", "text": "Compare these images." }),
+        ]
+    );
+}
+
+#[test]
+fn builder_reports_the_first_invalid_image() {
+    let result: Result<Message, _> = MessageBuilder::user()
+        .image_bytes("image/png", JPEG_BYTES)
+        .image_base64("image/tiff", "AAAA")
+        .text("What is this?")
+        .build();
+
+    let Err(AnthropicError::Validation(message)) = result else {
+        panic!("expected a validation error");
+    };
+    assert!(message.contains("declared image/png but bytes look like JPEG"));
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::{ContentBlock, Message},
+    messages::{MessageRequest, StopReason},
+    transport::MockTransport,
+};
+use serde_json::json;
+use std::time::Duration;
+
+fn request() -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()]).max_tokens(64)
+}
+
+#[tokio::test]
+async fn scripted_responses_are_returned_and_requests_recorded() {
+    let transport = MockTransport::new()
+        .respond(
+            529,
+            &json!({"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}),
+        )
+        .respond(
+            200,
+            &json!({"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}}),
+        );
+    let client = AnthropicClient::builder("http://mock/v1", "key")
+        .max_retries(1)
+        .base_delay(Duration::from_millis(1))
+        .build()
+        .unwrap()
+        .transport(transport.clone());
+
+    let response = client.messages().create(request()).await.unwrap();
+    assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].method, "POST");
+    assert_eq!(requests[1].url, "http://mock/v1/messages");
+    assert!(requests[1]
+        .headers
+        .contains(&("x-api-key".to_owned(), "key".to_owned())));
+    let body = requests[1].json().unwrap();
+    assert_eq!(body["model"], "claude-sonnet-4-5");
+    assert_eq!(body["max_tokens"], 64);
+}
+
+#[tokio::test]
+async fn scripted_streams_are_returned() {
+    let transport = MockTransport::new().respond_stream(&[
+        json!({"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":1}}}),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello!"}}),
+        json!({"type":"content_block_stop","index":0}),
+        json!({"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":3}}),
+        json!({"type":"message_stop"}),
+    ]);
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let stream = client.messages().create_stream(request()).await.unwrap();
+    let message = stream.collect_message().await.unwrap();
+
+    assert_eq!(message.content, [ContentBlock::text("Hello!")]);
+    assert_eq!(transport.requests()[0].json().unwrap()["stream"], true);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    model::Model,
+    models::ModelInfo,
+    page::{ListParams, Page},
+    transport::MockTransport,
+};
+use serde_json::{json, Value};
+
+#[test]
+fn model_pages_are_parsed() {
+    let page: Page<ModelInfo> = serde_json::from_str(
+        r#"{
+            "data": [{
+                "type": "model",
+                "id": "claude-sonnet-4-5-20250929",
+                "display_name": "Claude Sonnet 4.5",
+                "created_at": "2025-09-29T00:00:00Z"
+            }],
+            "has_more": true,
+            "first_id": "claude-sonnet-4-5-20250929",
+            "last_id": "claude-sonnet-4-5-20250929"
+        }"#,
+    )
+    .unwrap();
+
+    assert!(page.has_more);
+    assert_eq!(page.data[0].display_name, "Claude Sonnet 4.5");
+    assert_eq!(page.data[0].model(), Model::ClaudeSonnet45);
+    assert_eq!(page.last_id.as_deref(), Some("claude-sonnet-4-5-20250929"));
+}
+
+fn model(id: &str) -> Value {
+    json!({"type":"model","id":id,"display_name":"Claude Opus 4.5","created_at":"2025-11-24T00:00:00Z"})
+}
+
+#[tokio::test]
+async fn models_are_listed_and_retrieved_from_any_base_url() {
+    let transport = MockTransport::new()
+        .respond(
+            200,
+            &json!({"data":[model("claude-opus-4-5-20251101")],"has_more":false,"first_id":"claude-opus-4-5-20251101","last_id":"claude-opus-4-5-20251101"}),
+        )
+        .respond(200, &model("claude-opus-4-5-20251101"))
+        .respond(
+            200,
+            &json!({"data":[],"has_more":false,"first_id":null,"last_id":null}),
+        );
+
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+    let params = ListParams::new().limit(5);
+    let page = client.models().list(&params).await.unwrap();
+    assert_eq!(page.data[0].display_name, "Claude Opus 4.5");
+    let info = client.models().retrieve("claude-opus-4-5").await.unwrap();
+    assert_eq!(info.id, "claude-opus-4-5-20251101");
+    assert_eq!(info.model(), Model::ClaudeOpus45);
+
+    let client = AnthropicClient::new("http://mock/v1/", "key").transport(transport.clone());
+    assert!(client
+        .models()
+        .list(&ListParams::new())
+        .await
+        .unwrap()
+        .data
+        .is_empty());
+
+    let urls: Vec<String> = transport
+        .requests()
+        .into_iter()
+        .map(|request| request.url)
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            "http://mock/v1/models?limit=5",
+            "http://mock/v1/models/claude-opus-4-5",
+            "http://mock/v1/models",
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+use rusty_anthropic::anthropic_api::{
+    cache::{cache_control, CachedPrompt},
+    content::{Message, MessageBuilder},
+    messages::{MessageRequest, MessageResponse},
+    tools::Tool,
+};
+use serde_json::json;
+
+#[test]
+fn breakpoints_end_each_stable_part() {
+    let prompt = CachedPrompt::new("claude-sonnet-4-5")
+        .system("Answer questions about the document.")
+        .tools(vec![
+            json!({ "name": "search", "input_schema": { "type": "object" } }),
+            json!({ "name": "quote", "input_schema": { "type": "object" } }),
+        ])
+        .context(vec![
+            json!({ "role": "user", "content": "<document>...</document>" }),
+        ]);
+
+    let request = prompt.request([json!({ "role": "user", "content": "Who is the author?" })]);
+    let request = serde_json::to_value(request).unwrap();
+    let ephemeral = json!({ "type": "ephemeral" });
+
+    assert!(request["tools"][0].get("cache_control").is_none());
+    assert_eq!(request["tools"][1]["cache_control"], ephemeral);
+    assert_eq!(request["system"][0]["cache_control"], ephemeral);
+    assert_eq!(
+        request["messages"][0]["content"][0]["text"],
+        "<document>...</document>"
+    );
+    assert_eq!(
+        request["messages"][0]["content"][0]["cache_control"],
+        ephemeral
+    );
+    assert_eq!(request["messages"][1]["content"], "Who is the author?");
+}
+
+#[test]
+fn blocks_are_marked_one_by_one() {
+    let ephemeral = json!({ "type": "ephemeral" });
+    let document = MessageBuilder::user()
+        .text("<document>...</document>")
+        .cache_control()
+        .text("Who is the author?")
+        .build()
+        .unwrap();
+    assert_eq!(document.content[0]["cache_control"], ephemeral);
+    assert!(document.content[1].get("cache_control").is_none());
+
+    let tool = cache_control(Tool::new("search", json!({ "type": "object" })));
+    assert_eq!(tool["cache_control"], ephemeral);
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .system_cached("Answer questions about the document.");
+    let request = serde_json::to_value(request).unwrap();
+    assert_eq!(
+        request["system"],
+        json!([{
+            "type": "text",
+            "text": "Answer questions about the document.",
+            "cache_control": ephemeral,
+        }])
+    );
+
+    let response: MessageResponse = serde_json::from_value(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": [{ "type": "text", "text": "Jane Doe." }],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": 12,
+            "output_tokens": 4,
+            "cache_creation_input_tokens": 0,
+            "cache_read_input_tokens": 2048,
+        },
+    }))
+    .unwrap();
+    assert_eq!(response.usage.cache_read_input_tokens, Some(2048));
+}
@@ -0,0 +1,84 @@
+use reqwest::Proxy;
+use rusty_anthropic::anthropic_api::{client::AnthropicClient, error::AnthropicError};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::oneshot,
+};
+
+/// Act as a proxy for a single request, answering it with an empty JSON
+/// object and returning the request head it received.
+async fn proxy_once() -> (String, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (sender, receiver) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let read = socket.read(&mut buffer).await.unwrap();
+        let head = String::from_utf8_lossy(&buffer[..read]).into_owned();
+
+        if !head.starts_with("CONNECT") {
+            let response = "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                            content-length: 2\r\nconnection: close\r\n\r\n{}";
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+        sender.send(head).unwrap();
+    });
+
+    (address, receiver)
+}
+
+#[tokio::test]
+async fn http_requests_go_through_the_proxy_with_its_credentials() {
+    let (address, head) = proxy_once().await;
+    let client = AnthropicClient::builder("http://api.example.test/v1", "key")
+        .proxy(Proxy::all(format!("http://user:secret@{address}")).unwrap())
+        .build()
+        .unwrap();
+
+    let response: Value = client.post("/messages", &json!({})).await.unwrap();
+    assert_eq!(response, json!({}));
+
+    let head = head.await.unwrap().to_lowercase();
+    assert!(head.starts_with("post http://api.example.test/v1/messages http/1.1"));
+    // base64 of `user:secret`.
+    assert!(head.contains("proxy-authorization: basic dxnlcjpzzwnyzxq="));
+}
+
+#[tokio::test]
+async fn https_requests_are_tunnelled_through_the_proxy() {
+    let (address, head) = proxy_once().await;
+    let client = AnthropicClient::builder("https://api.example.test/v1", "key")
+        .proxy(
+            Proxy::https(format!("http://{address}"))
+                .unwrap()
+                .basic_auth("user", "secret"),
+        )
+        .build()
+        .unwrap();
+
+    // The proxy closes the tunnel, so only the CONNECT request is observed.
+    assert!(client
+        .post::<_, Value>("/messages", &json!({}))
+        .await
+        .is_err());
+
+    let head = head.await.unwrap();
+    assert!(head.starts_with("CONNECT api.example.test:443 HTTP/1.1"));
+    assert!(!head.contains("x-api-key"));
+    assert!(head
+        .to_lowercase()
+        .contains("proxy-authorization: basic dxnlcjpzzwnyzxq="));
+}
+
+#[test]
+fn proxies_cannot_be_combined_with_a_preconfigured_client() {
+    let client = AnthropicClient::builder("http://localhost", "key")
+        .client(reqwest::Client::new())
+        .proxy(Proxy::all("http://127.0.0.1:3128").unwrap())
+        .build();
+    assert!(matches!(client, Err(AnthropicError::Validation(_))));
+}
@@ -0,0 +1,34 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+use rusty_anthropic::anthropic_api::meta::{RateLimit, RateLimitInfo};
+use std::time::Duration;
+
+#[test]
+fn rate_limit_headers_are_parsed() {
+    let mut headers = HeaderMap::new();
+    for (name, value) in [
+        ("anthropic-ratelimit-requests-limit", "50"),
+        ("anthropic-ratelimit-requests-remaining", "49"),
+        ("anthropic-ratelimit-requests-reset", "2024-08-01T12:00:01Z"),
+        ("anthropic-ratelimit-tokens-remaining", "not a number"),
+        ("retry-after", "3"),
+    ] {
+        headers.insert(name, HeaderValue::from_static(value));
+    }
+
+    let info = RateLimitInfo::from_headers(&headers);
+    assert_eq!(
+        info.requests,
+        Some(RateLimit {
+            limit: Some(50),
+            remaining: Some(49),
+            reset: Some("2024-08-01T12:00:01Z".to_owned()),
+        })
+    );
+    assert_eq!(info.tokens, None);
+    assert_eq!(info.input_tokens, None);
+    assert_eq!(info.retry_after, Some(Duration::from_secs(3)));
+    assert_eq!(
+        RateLimitInfo::from_headers(&HeaderMap::new()),
+        RateLimitInfo::default()
+    );
+}
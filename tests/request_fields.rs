@@ -0,0 +1,109 @@
+use rusty_anthropic::anthropic_api::messages::{
+    MessageRequest, MessageResponse, Metadata, ServiceTier, UsedServiceTier,
+};
+use serde_json::{json, Value};
+
+#[test]
+fn set_fields_lists_only_the_parameters_set() {
+    let messages = vec![json!({ "role": "user", "content": "Hello!" })];
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), messages.clone());
+    assert!(request.set_fields().is_empty());
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), messages)
+        .system("Be brief.".into())
+        .max_tokens(256)
+        .temperature(0.2);
+    assert_eq!(
+        request.set_fields(),
+        ["max_tokens", "temperature", "system"]
+    );
+}
+
+#[test]
+fn system_prompt_is_sent_as_a_string_or_blocks() {
+    let messages = vec![json!({ "role": "user", "content": "Hello!" })];
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), messages.clone())
+        .system("Be brief.".into());
+    assert_eq!(
+        serde_json::to_value(request).unwrap()["system"],
+        "Be brief."
+    );
+
+    let blocks = vec![json!({
+        "type": "text",
+        "text": "Be brief.",
+        "cache_control": { "type": "ephemeral" },
+    })];
+    let request =
+        MessageRequest::new("claude-sonnet-4-5".into(), messages).system_blocks(blocks.clone());
+    assert_eq!(
+        serde_json::to_value(request).unwrap()["system"],
+        json!(blocks)
+    );
+}
+
+#[test]
+fn with_messages_keeps_the_other_settings() {
+    let request = MessageRequest::new(
+        "claude-sonnet-4-5".into(),
+        vec![json!({ "role": "user", "content": "Hello!" })],
+    )
+    .system("Be brief.".into())
+    .max_tokens(256)
+    .temperature(0.2);
+
+    let rephrased = request
+        .clone()
+        .with_messages(vec![json!({ "role": "user", "content": "Hi there!" })]);
+    let original = serde_json::to_value(request).unwrap();
+    let rephrased = serde_json::to_value(rephrased).unwrap();
+
+    assert_eq!(original["messages"][0]["content"], "Hello!");
+    assert_eq!(rephrased["messages"][0]["content"], "Hi there!");
+    for field in ["model", "system", "max_tokens", "temperature"] {
+        assert_eq!(original[field], rephrased[field]);
+    }
+}
+
+#[test]
+fn metadata_carries_the_user_id() {
+    let request = MessageRequest::new(
+        "claude-sonnet-4-5".into(),
+        vec![json!({ "role": "user", "content": "Hello!" })],
+    )
+    .metadata(Metadata::user("user-5f3a").into());
+
+    assert_eq!(
+        serde_json::to_value(request).unwrap()["metadata"],
+        json!({ "user_id": "user-5f3a" })
+    );
+    assert_eq!(Value::from(Metadata::default()), json!({}));
+}
+
+#[test]
+fn service_tier_is_sent_and_the_tier_used_returned() {
+    let request = MessageRequest::new(
+        "claude-sonnet-4-5".into(),
+        vec![json!({ "role": "user", "content": "Hello!" })],
+    )
+    .service_tier(ServiceTier::StandardOnly);
+    assert_eq!(
+        serde_json::to_value(request).unwrap()["service_tier"],
+        "standard_only"
+    );
+
+    let response: MessageResponse = serde_json::from_value(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": [],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 8, "output_tokens": 3, "service_tier": "priority" },
+    }))
+    .unwrap();
+    assert_eq!(response.usage.service_tier, Some(UsedServiceTier::Priority));
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::{AnthropicClient, DEFAULT_API_VERSION, DEFAULT_USER_AGENT},
+    content::Message,
+    messages::MessageRequest,
+    transport::MockTransport,
+};
+use serde_json::json;
+
+/// Send a request through `client`, returning the headers it carried.
+async fn sent_headers(client: AnthropicClient) -> Vec<(String, String)> {
+    let transport = MockTransport::new().respond(
+        200,
+        &json!({"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}}),
+    );
+    let client = client.transport(transport.clone());
+
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(16);
+    client.messages().create(request).await.unwrap();
+
+    transport.requests().pop().unwrap().headers
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[tokio::test]
+async fn api_version_defaults_and_can_be_overridden() {
+    let client = AnthropicClient::new("http://localhost", "key");
+    let headers = sent_headers(client).await;
+    assert_eq!(
+        header(&headers, "anthropic-version"),
+        Some(DEFAULT_API_VERSION)
+    );
+
+    let client = AnthropicClient::new("http://localhost", "key").api_version("2099-01-01");
+    let headers = sent_headers(client).await;
+    assert_eq!(header(&headers, "anthropic-version"), Some("2099-01-01"));
+}
+
+#[tokio::test]
+async fn betas_are_sent_comma_separated() {
+    let client = AnthropicClient::new("http://localhost", "key");
+    let headers = sent_headers(client).await;
+    assert_eq!(header(&headers, "anthropic-beta"), None);
+
+    let client = AnthropicClient::new("http://localhost", "key")
+        .beta("prompt-caching-2024-07-31")
+        .beta("output-128k-2025-02-19")
+        .beta("prompt-caching-2024-07-31");
+    let headers = sent_headers(client).await;
+    assert_eq!(
+        header(&headers, "anthropic-beta"),
+        Some("prompt-caching-2024-07-31,output-128k-2025-02-19")
+    );
+}
+
+#[tokio::test]
+async fn user_agent_defaults_to_the_crate_version() {
+    let client = AnthropicClient::new("http://localhost", "key");
+    let headers = sent_headers(client).await;
+    assert_eq!(header(&headers, "user-agent"), Some(DEFAULT_USER_AGENT));
+    assert!(DEFAULT_USER_AGENT.starts_with("rusty-anthropic/"));
+
+    let client = AnthropicClient::builder("http://localhost", "key")
+        .user_agent("gateway-test/2.0".to_owned())
+        .build()
+        .unwrap();
+    let headers = sent_headers(client).await;
+    assert_eq!(header(&headers, "user-agent"), Some("gateway-test/2.0"));
+}
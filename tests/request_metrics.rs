@@ -0,0 +1,160 @@
+#![cfg(all(feature = "metrics", feature = "test-util"))]
+
+use metrics::{
+    Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    telemetry::{REQUESTS_TOTAL, REQUEST_DURATION_SECONDS, REQUEST_RETRIES_TOTAL},
+    transport::MockTransport,
+};
+use serde_json::{json, Value};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Sorted labels of a metric.
+type Labels = Vec<(String, String)>;
+
+/// Metric recorded by [`Capture`]: name, labels, and values.
+type Recorded = (String, Labels, Vec<f64>);
+
+/// Recorder capturing every counter increment and histogram sample.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Recorded>>>);
+
+struct Handle(Arc<Mutex<Vec<Recorded>>>, String, Labels);
+
+impl Handle {
+    fn push(&self, value: f64) {
+        let mut recorded = self.0.lock().unwrap();
+        match recorded
+            .iter_mut()
+            .find(|(name, labels, _)| *name == self.1 && *labels == self.2)
+        {
+            Some((.., values)) => values.push(value),
+            None => recorded.push((self.1.clone(), self.2.clone(), vec![value])),
+        }
+    }
+}
+
+impl CounterFn for Handle {
+    fn increment(&self, value: u64) {
+        self.push(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.push(value as f64);
+    }
+}
+
+impl HistogramFn for Handle {
+    fn record(&self, value: f64) {
+        self.push(value);
+    }
+}
+
+impl Capture {
+    fn handle(&self, key: &Key) -> Arc<Handle> {
+        let mut labels: Vec<_> = key
+            .labels()
+            .map(|label| (label.key().to_owned(), label.value().to_owned()))
+            .collect();
+        labels.sort();
+        Arc::new(Handle(self.0.clone(), key.name().to_owned(), labels))
+    }
+
+    fn values(&self, name: &str) -> Vec<(Labels, Vec<f64>)> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(recorded, ..)| recorded == name)
+            .map(|(_, labels, values)| (labels.clone(), values.clone()))
+            .collect()
+    }
+}
+
+impl Recorder for Capture {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.handle(key))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.handle(key))
+    }
+}
+
+fn labels(pairs: &[(&str, &str)]) -> Labels {
+    pairs
+        .iter()
+        .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+        .collect()
+}
+
+#[test]
+fn retried_requests_are_counted_once_under_their_route() {
+    let overloaded =
+        json!({"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}});
+    let transport = MockTransport::new()
+        .respond(529, &overloaded)
+        .respond(529, &overloaded)
+        .respond(200, &json!({"id": "msgbatch_01"}));
+    let client = AnthropicClient::builder("http://mock/v1", "key")
+        .max_retries(2)
+        .base_delay(Duration::from_millis(1))
+        .build()
+        .unwrap()
+        .transport(transport.clone());
+    let capture = Capture::default();
+
+    metrics::with_local_recorder(&capture, || {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(client.get::<Value>("/messages/batches/msgbatch_01/results?limit=5"))
+            .unwrap();
+    });
+    assert_eq!(transport.requests().len(), 3);
+
+    let endpoint = "/messages/batches/{id}/results";
+    assert_eq!(
+        capture.values(REQUESTS_TOTAL),
+        [(
+            labels(&[
+                ("endpoint", endpoint),
+                ("model", "unknown"),
+                ("status", "200")
+            ]),
+            vec![1.0]
+        )]
+    );
+    assert_eq!(
+        capture.values(REQUEST_RETRIES_TOTAL),
+        [(
+            labels(&[("endpoint", endpoint), ("model", "unknown")]),
+            vec![1.0, 1.0]
+        )]
+    );
+
+    let durations = capture.values(REQUEST_DURATION_SECONDS);
+    assert_eq!(durations.len(), 1);
+    assert_eq!(
+        durations[0].0,
+        labels(&[("endpoint", endpoint), ("model", "unknown")])
+    );
+    assert_eq!(durations[0].1.len(), 1);
+}
@@ -0,0 +1,84 @@
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient, content::Message, error::AnthropicError, messages::MessageRequest,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[tokio::test]
+async fn per_request_timeout_fails_with_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let _ = socket.read(&mut buffer).await;
+        tokio::time::sleep(Duration::from_secs(30)).await;
+    });
+
+    let client = AnthropicClient::builder(base_url, "key")
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap();
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(16);
+
+    let timeout = Duration::from_millis(100);
+    let error = client
+        .messages()
+        .create_with_timeout(request, timeout)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AnthropicError::Timeout(elapsed) if elapsed == timeout));
+}
+
+#[tokio::test]
+async fn retries_do_not_outlast_the_request_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let _ = socket.read(&mut buffer).await;
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let body =
+                r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+            let response = format!(
+                "HTTP/1.1 529 X\r\ncontent-type: application/json\r\n\
+                 content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    let client = AnthropicClient::builder(base_url, "key")
+        .max_retries(3)
+        .base_delay(Duration::from_secs(2))
+        .build()
+        .unwrap();
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(16);
+
+    let started = Instant::now();
+    let error = client
+        .messages()
+        .create_with_timeout(request, Duration::from_millis(200))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AnthropicError::Api { status, .. } if status == 529));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
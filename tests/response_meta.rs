@@ -0,0 +1,42 @@
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient, content::Message, messages::MessageRequest,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+#[tokio::test]
+async fn create_with_meta_returns_the_typed_response_and_its_headers() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let _ = socket.read(&mut buffer).await.unwrap();
+
+        let body = r#"{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":3}}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\nrequest-id: req_01\r\n\
+             anthropic-ratelimit-requests-remaining: 49\r\ncontent-length: {}\r\n\
+             connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let client = AnthropicClient::new(base_url, "key");
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(16);
+
+    let (response, meta) = client.messages().create_with_meta(request).await.unwrap();
+    assert_eq!(response.id, "msg_01");
+    assert_eq!(response.text(), "Hello!");
+    assert_eq!(response.usage.output_tokens, 3);
+    assert_eq!(meta.request_id.as_deref(), Some("req_01"));
+    assert_eq!(
+        meta.rate_limit.requests.and_then(|limit| limit.remaining),
+        Some(49)
+    );
+    assert_eq!(meta.timing, None);
+}
@@ -0,0 +1,39 @@
+use rusty_anthropic::anthropic_api::messages::MessageResponse;
+use serde_json::{json, Value};
+
+fn response(content: Value) -> MessageResponse {
+    serde_json::from_value(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": content,
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 8, "output_tokens": 3 },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn text_joins_text_blocks_only() {
+    let response = response(json!([
+        { "type": "thinking", "thinking": "The user greets me.", "signature": "c2ln" },
+        { "type": "text", "text": "Hello! " },
+        { "type": "tool_use", "id": "toolu_01", "name": "get_time", "input": {} },
+        { "type": "text", "text": "Let me check the time." },
+    ]));
+
+    assert_eq!(response.text(), "Hello! Let me check the time.");
+    assert_eq!(response.first_text(), Some("Hello! "));
+}
+
+#[test]
+fn responses_without_text_have_none() {
+    let response = response(json!([
+        { "type": "tool_use", "id": "toolu_01", "name": "get_time", "input": {} },
+    ]));
+
+    assert_eq!(response.text(), "");
+    assert_eq!(response.first_text(), None);
+}
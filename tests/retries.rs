@@ -0,0 +1,98 @@
+use rusty_anthropic::anthropic_api::{client::AnthropicClient, error::AnthropicError};
+use serde_json::{json, Value};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Serve `responses` in order, one per connection, and count the requests.
+async fn serve(responses: Vec<(u16, &'static str, &'static str)>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        for (status, extra_headers, body) in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let _ = socket.read(&mut buffer).await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let response = format!(
+                "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\n{extra_headers}\
+                 content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (base_url, requests)
+}
+
+const OVERLOADED: &str =
+    r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+const RATE_LIMITED: &str =
+    r#"{"type":"error","error":{"type":"rate_limit_error","message":"Slow down"}}"#;
+const INVALID: &str =
+    r#"{"type":"error","error":{"type":"invalid_request_error","message":"Bad"}}"#;
+
+#[tokio::test]
+async fn overloaded_and_rate_limited_requests_are_retried() {
+    let (base_url, requests) = serve(vec![
+        (529, "", OVERLOADED),
+        (429, "retry-after: 0.05\r\n", RATE_LIMITED),
+        (200, "", r#"{"ok":true}"#),
+    ])
+    .await;
+    let client = AnthropicClient::builder(base_url, "key")
+        .max_retries(2)
+        .base_delay(Duration::from_millis(10))
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    let response: Value = client.post("/messages", &json!({})).await.unwrap();
+    assert_eq!(response, json!({"ok": true}));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn client_errors_and_exhausted_retries_are_returned() {
+    let (base_url, requests) = serve(vec![(400, "", INVALID)]).await;
+    let client = AnthropicClient::builder(base_url, "key")
+        .max_retries(3)
+        .base_delay(Duration::from_millis(1))
+        .build()
+        .unwrap();
+
+    let error = client
+        .post::<_, Value>("/messages", &json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AnthropicError::Api { status, .. } if status == 400));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    let (base_url, requests) = serve(vec![(503, "", OVERLOADED), (503, "", OVERLOADED)]).await;
+    let client = AnthropicClient::builder(base_url, "key")
+        .max_retries(1)
+        .base_delay(Duration::from_millis(1))
+        .build()
+        .unwrap();
+
+    let error = client
+        .post::<_, Value>("/messages", &json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AnthropicError::Api { status, .. } if status == 503));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
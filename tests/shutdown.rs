@@ -0,0 +1,46 @@
+#![cfg(feature = "test-util")]
+
+use futures_core::Stream;
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient, content::Message, error::AnthropicError, messages::MessageRequest,
+    transport::MockTransport,
+};
+use serde_json::json;
+use std::{future::poll_fn, pin::Pin, time::Duration};
+
+fn request() -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()]).max_tokens(64)
+}
+
+fn transport() -> MockTransport {
+    MockTransport::new().respond_stream(&[
+        json!({"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":1}}}),
+        json!({"type":"message_stop"}),
+    ])
+}
+
+#[tokio::test]
+async fn open_streams_count_as_in_flight_until_dropped() {
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport());
+
+    let stream = client.messages().create_stream(request()).await.unwrap();
+    assert_eq!(client.in_flight(), 1);
+
+    drop(stream);
+    assert_eq!(client.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn shutdown_cancels_open_streams() {
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport());
+    let mut stream = client.messages().create_stream(request()).await.unwrap();
+
+    assert!(!client.shutdown(Duration::from_millis(10)).await);
+
+    let event = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+    assert!(matches!(event, Some(Err(AnthropicError::Cancelled))));
+    assert!(poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))
+        .await
+        .is_none());
+    assert_eq!(client.in_flight(), 0);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "test-util")]
+
+use futures_core::Stream;
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient, content::Message, messages::MessageRequest, transport::MockTransport,
+};
+use serde_json::json;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    future::poll_fn,
+    pin::Pin,
+};
+
+/// Allocator counting the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Count the allocations made while reading a stream of `frames` pings.
+async fn allocations_reading(frames: usize) -> usize {
+    let events = vec![json!({ "type": "ping" }); frames];
+    let transport = MockTransport::new().respond_stream(&events);
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport);
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(16);
+    let mut stream = client.messages().create_stream(request).await.unwrap();
+
+    // Read the first event before counting, so the body is buffered.
+    poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+    let before = ALLOCATIONS.with(Cell::get);
+    while let Some(event) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+        event.unwrap();
+    }
+
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[tokio::test]
+async fn decoding_frames_does_not_allocate_per_frame() {
+    let few = allocations_reading(1_000).await;
+    let many = allocations_reading(10_000).await;
+    println!("allocations: {few} for 1,000 frames, {many} for 10,000 frames");
+
+    // Only warming up the buffers allocates, however many frames are read.
+    assert!(many <= few + 16, "{many} allocations for 10,000 frames");
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::Message,
+    error::AnthropicError,
+    messages::{MessageRequest, StopReason},
+    text_completions::TextCompletionRequest,
+    transport::MockTransport,
+};
+use serde_json::json;
+
+fn message_request() -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()]).max_tokens(64)
+}
+
+#[tokio::test]
+async fn single_stop_sequences_are_appended() {
+    let transport = MockTransport::new()
+        .respond(
+            200,
+            &json!({"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Hello"}],"stop_reason":"stop_sequence","stop_sequence":"STOP","usage":{"input_tokens":8,"output_tokens":2}}),
+        )
+        .respond(
+            200,
+            &json!({"type":"completion","id":"compl_01","completion":" Hello","stop_reason":"stop_sequence","model":"claude-2.1"}),
+        );
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let request = message_request()
+        .stop_sequences(vec!["END".into()])
+        .stop_sequence("STOP");
+    let response = client.messages().create(request).await.unwrap();
+    assert_eq!(response.stop_reason, Some(StopReason::StopSequence));
+    assert_eq!(response.stop_sequence.as_deref(), Some("STOP"));
+
+    let request = TextCompletionRequest::new("claude-2.1".into(), "\n\nHuman: Hi".into())
+        .stop_sequence("\n\nHuman:");
+    let completion = client.text_completions().create(request).await.unwrap();
+    assert_eq!(completion["stop_reason"], "stop_sequence");
+
+    let requests = transport.requests();
+    assert_eq!(
+        requests[0].json().unwrap()["stop_sequences"],
+        json!(["END", "STOP"])
+    );
+    assert_eq!(
+        requests[1].json().unwrap()["stop_sequences"],
+        json!(["\n\nHuman:"])
+    );
+}
+
+#[tokio::test]
+async fn blank_stop_sequences_are_rejected_before_sending() {
+    let transport = MockTransport::new();
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let request = message_request().stop_sequence("END").stop_sequence(" ");
+    let error = client.messages().create(request).await.unwrap_err();
+    assert!(
+        matches!(error, AnthropicError::Validation(message) if message.contains("stop_sequences.1"))
+    );
+
+    let request =
+        TextCompletionRequest::new("claude-2.1".into(), "\n\nHuman: Hi".into()).stop_sequence("");
+    let error = client.text_completions().create(request).await.unwrap_err();
+    assert!(matches!(error, AnthropicError::Validation(_)));
+
+    assert!(transport.requests().is_empty());
+}
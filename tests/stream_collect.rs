@@ -0,0 +1,130 @@
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::{ContentBlock, Message, ToolUse},
+    error::{AnthropicError, Result},
+    messages::{MessageRequest, MessageResponse, StopReason},
+};
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Stream `events` as the server-sent events of a single response, then
+/// collect the message.
+async fn collect(events: &[serde_json::Value]) -> Result<MessageResponse> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+    let body: String = events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {event}\n\n",
+                event["type"].as_str().unwrap()
+            )
+        })
+        .collect();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0; 8192];
+        let _ = socket.read(&mut buffer).await.unwrap();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\n\
+             connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let client = AnthropicClient::new(base_url, "key");
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(256);
+    client
+        .messages()
+        .create_stream(request)
+        .await?
+        .collect_message()
+        .await
+}
+
+fn message_start() -> serde_json::Value {
+    json!({"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":80,"output_tokens":1}}})
+}
+
+#[tokio::test]
+async fn interleaved_blocks_are_collected_into_the_final_message() {
+    let message = collect(&[
+        message_start(),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}),
+        json!({"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_01","name":"get_weather","input":{}}}),
+        json!({"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"city\": \"Pa"}}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Checking "}}),
+        json!({"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"ris\"}"}}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"the weather."}}),
+        json!({"type":"content_block_stop","index":1}),
+        json!({"type":"content_block_stop","index":0}),
+        json!({"type":"ping"}),
+        json!({"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":42}}),
+        json!({"type":"message_stop"}),
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        message.content,
+        [
+            ContentBlock::text("Checking the weather."),
+            ContentBlock::ToolUse(ToolUse {
+                id: "toolu_01".into(),
+                name: "get_weather".into(),
+                input: json!({ "city": "Paris" }),
+            }),
+        ]
+    );
+    assert_eq!(message.stop_reason, Some(StopReason::ToolUse));
+    assert_eq!(message.usage.input_tokens, 80);
+    assert_eq!(message.usage.output_tokens, 42);
+}
+
+#[tokio::test]
+async fn errors_and_truncated_streams_are_reported() {
+    let error = collect(&[
+        message_start(),
+        json!({"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}),
+    ])
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        AnthropicError::Api { status, error_type, .. }
+            if status.as_u16() == 529 && error_type == "overloaded_error"
+    ));
+
+    let error = collect(&[
+        message_start(),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}),
+    ])
+    .await
+    .unwrap_err();
+    assert!(matches!(error, AnthropicError::IncompleteStream));
+}
+
+#[tokio::test]
+async fn events_of_unknown_type_are_skipped() {
+    let message = collect(&[
+        message_start(),
+        json!({"type":"future_event","index":0,"payload":{"anything":true}}),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello!"}}),
+        json!({"type":"content_block_stop","index":0}),
+        json!({"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":3}}),
+        json!({"type":"message_stop"}),
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(message.content, [ContentBlock::text("Hello!")]);
+    assert_eq!(message.stop_reason, Some(StopReason::EndTurn));
+}
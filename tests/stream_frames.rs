@@ -0,0 +1,53 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient, content::Message, error::AnthropicError, messages::MessageRequest,
+    transport::MockTransport,
+};
+use serde_json::{json, Value};
+
+/// Events of a message whose text is streamed in `deltas` deltas of `text`.
+fn events(deltas: usize, text: &str) -> Vec<Value> {
+    let mut events = vec![
+        json!({"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":1}}}),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}),
+    ];
+    events.extend((0..deltas).map(|_| {
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":text}})
+    }));
+    events.extend([
+        json!({"type":"content_block_stop","index":0}),
+        json!({"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":500}}),
+        json!({"type":"message_stop"}),
+    ]);
+    events
+}
+
+/// Stream `events` in a single chunk, with frames of at most 512 bytes.
+async fn collect(events: &[Value]) -> Result<String, AnthropicError> {
+    let transport = MockTransport::new().respond_stream(events);
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport);
+    let request = MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(1024);
+
+    let stream = client.messages().create_stream(request).await?;
+    Ok(stream.max_frame_size(512).collect_message().await?.text())
+}
+
+#[tokio::test]
+async fn chunks_of_many_small_frames_fit_the_frame_size() {
+    // The single chunk holds some 50 kB, a hundred times the frame size.
+    let text = collect(&events(500, "word ")).await.unwrap();
+    assert_eq!(text, "word ".repeat(500));
+}
+
+#[tokio::test]
+async fn frames_above_the_frame_size_are_rejected() {
+    let error = collect(&events(1, &"x".repeat(1024))).await.unwrap_err();
+    assert!(matches!(
+        error,
+        AnthropicError::FrameTooLarge {
+            max_frame_size: 512
+        }
+    ));
+}
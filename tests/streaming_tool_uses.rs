@@ -0,0 +1,52 @@
+use rusty_anthropic::anthropic_api::streaming::{MessageAccumulator, StreamEvent};
+use serde_json::json;
+
+/// Events of a turn where the model streams two tool calls at once.
+fn interleaved_tool_calls() -> Vec<StreamEvent> {
+    [
+        json!({"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":80,"output_tokens":1}}}),
+        json!({"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_paris","name":"get_weather","input":{}}}),
+        json!({"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_tokyo","name":"get_weather","input":{}}}),
+        json!({"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"city\": \"To"}}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"city\": \"Pa"}}),
+        json!({"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"kyo\"}"}}),
+        json!({"type":"content_block_stop","index":1}),
+        json!({"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"ris\"}"}}),
+        json!({"type":"content_block_stop","index":0}),
+        json!({"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":40}}),
+        json!({"type":"message_stop"}),
+    ]
+    .into_iter()
+    .map(|event| serde_json::from_value(event).unwrap())
+    .collect()
+}
+
+#[test]
+fn concurrent_tool_calls_are_all_captured() {
+    let mut accumulator = MessageAccumulator::new();
+    for event in interleaved_tool_calls() {
+        accumulator.push(&event);
+    }
+
+    let tool_uses = accumulator.tool_uses();
+    assert_eq!(tool_uses.len(), 2);
+    assert_eq!(tool_uses[0].id, "toolu_paris");
+    assert_eq!(tool_uses[0].input, json!({ "city": "Paris" }));
+    assert_eq!(tool_uses[1].id, "toolu_tokyo");
+    assert_eq!(tool_uses[1].input, json!({ "city": "Tokyo" }));
+
+    assert_eq!(accumulator.finish().unwrap().tool_uses(), tool_uses);
+}
+
+#[test]
+fn tool_calls_still_streaming_are_left_out() {
+    let mut accumulator = MessageAccumulator::new();
+    for event in interleaved_tool_calls().iter().take(7) {
+        accumulator.push(event);
+    }
+
+    let tool_uses = accumulator.tool_uses();
+    assert_eq!(tool_uses.len(), 1);
+    assert_eq!(tool_uses[0].id, "toolu_tokyo");
+    assert_eq!(tool_uses[0].input, json!({ "city": "Tokyo" }));
+}
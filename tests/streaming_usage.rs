@@ -0,0 +1,123 @@
+use rusty_anthropic::anthropic_api::{
+    messages::{MessageResponse, StopReason},
+    streaming::{EventStats, MessageAccumulator, StreamEvent},
+};
+
+/// Stream recorded for a prompt-cached request, as sent over the wire.
+const RECORDED_STREAM: &str = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_01XFDUDYJgAACzvnptvVoYEL","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":472,"cache_creation_input_tokens":0,"cache_read_input_tokens":2048,"output_tokens":2}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+
+event: ping
+data: {"type": "ping"}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello"}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"!"}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":null,"stop_sequence":null},"usage":{"output_tokens":9}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":15}}
+
+event: message_stop
+data: {"type":"message_stop"}
+"#;
+
+/// Non-streaming response for the same request.
+const NON_STREAMING_RESPONSE: &str = r#"{"id":"msg_01XFDUDYJgAACzvnptvVoYEL","type":"message","role":"assistant","content":[{"type":"text","text":"Hello!"}],"model":"claude-sonnet-4-20250514","stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":472,"cache_creation_input_tokens":0,"cache_read_input_tokens":2048,"output_tokens":15}}"#;
+
+fn events(stream: &str) -> Vec<StreamEvent> {
+    stream
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
+
+#[test]
+fn streamed_usage_matches_non_streaming_usage() {
+    let mut accumulator = MessageAccumulator::new();
+    for event in events(RECORDED_STREAM) {
+        accumulator.push(&event);
+    }
+    let streamed = accumulator.finish().unwrap();
+    let expected: MessageResponse = serde_json::from_str(NON_STREAMING_RESPONSE).unwrap();
+
+    assert_eq!(streamed.usage, expected.usage);
+    assert_eq!(streamed.usage.output_tokens, 15);
+    assert_eq!(streamed.stop_reason, Some(StopReason::EndTurn));
+    assert_eq!(streamed.content, expected.content);
+}
+
+#[test]
+fn message_delta_input_tokens_override_message_start() {
+    let stream = RECORDED_STREAM.replace(
+        r#""usage":{"output_tokens":15}"#,
+        r#""usage":{"input_tokens":480,"cache_read_input_tokens":2040,"output_tokens":15}"#,
+    );
+
+    let mut accumulator = MessageAccumulator::new();
+    for event in events(&stream) {
+        accumulator.push(&event);
+    }
+    let usage = accumulator.finish().unwrap().usage;
+
+    assert_eq!(usage.input_tokens, 480);
+    assert_eq!(usage.cache_read_input_tokens, Some(2040));
+    assert_eq!(usage.cache_creation_input_tokens, Some(0));
+    assert_eq!(usage.output_tokens, 15);
+}
+
+#[test]
+fn events_are_counted_by_type() {
+    let mut accumulator = MessageAccumulator::new();
+    for event in events(RECORDED_STREAM) {
+        accumulator.push(&event);
+    }
+
+    let stats = accumulator.stats();
+    assert_eq!(
+        stats,
+        EventStats {
+            message_start: 1,
+            content_block_start: 1,
+            content_block_delta: 2,
+            content_block_stop: 1,
+            message_delta: 2,
+            message_stop: 1,
+            ping: 1,
+            error: 0,
+            unknown: 0,
+        }
+    );
+    assert_eq!(stats.total(), 9);
+}
+
+#[test]
+fn context_window_exceeded_is_the_final_stop_reason() {
+    let stream = RECORDED_STREAM.replace(
+        r#""stop_reason":"end_turn""#,
+        r#""stop_reason":"model_context_window_exceeded""#,
+    );
+
+    let mut accumulator = MessageAccumulator::new();
+    for event in events(&stream) {
+        accumulator.push(&event);
+    }
+    let message = accumulator.finish().unwrap();
+
+    assert_eq!(
+        message.stop_reason,
+        Some(StopReason::ModelContextWindowExceeded)
+    );
+    assert!(message.context_window_exceeded());
+}
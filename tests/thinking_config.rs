@@ -0,0 +1,62 @@
+#![cfg(feature = "test-util")]
+
+use rusty_anthropic::anthropic_api::{
+    client::AnthropicClient,
+    content::{ContentBlock, Message},
+    error::AnthropicError,
+    messages::{MessageRequest, ThinkingConfig},
+    transport::MockTransport,
+};
+use serde_json::json;
+
+fn request(max_tokens: u64, thinking: ThinkingConfig) -> MessageRequest {
+    MessageRequest::new("claude-sonnet-4-5".into(), vec![Message::user("Hi").into()])
+        .max_tokens(max_tokens)
+        .thinking(thinking)
+}
+
+#[tokio::test]
+async fn thinking_config_is_sent_with_the_request() {
+    let transport = MockTransport::new().respond(
+        200,
+        &json!({"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"thinking","thinking":"A greeting.","signature":"EqQBCg"},{"type":"text","text":"Hello!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":8,"output_tokens":12}}),
+    );
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    let response = client
+        .messages()
+        .create(request(8_192, ThinkingConfig::enabled(4_096)))
+        .await
+        .unwrap();
+    assert!(matches!(
+        &response.content[0],
+        ContentBlock::Thinking { thinking, signature } if thinking == "A greeting." && signature == "EqQBCg"
+    ));
+    assert_eq!(response.text(), "Hello!");
+
+    let body = transport.requests()[0].json().unwrap();
+    assert_eq!(
+        body["thinking"],
+        json!({ "type": "enabled", "budget_tokens": 4_096 })
+    );
+    assert_eq!(
+        serde_json::to_value(ThinkingConfig::Disabled).unwrap(),
+        json!({ "type": "disabled" })
+    );
+}
+
+#[tokio::test]
+async fn thinking_budget_is_validated_before_sending() {
+    let transport = MockTransport::new();
+    let client = AnthropicClient::new("http://mock/v1", "key").transport(transport.clone());
+
+    for thinking in [ThinkingConfig::enabled(4_096), ThinkingConfig::enabled(512)] {
+        let error = client
+            .messages()
+            .create(request(4_096, thinking))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AnthropicError::Validation(_)));
+    }
+    assert!(transport.requests().is_empty());
+}
@@ -0,0 +1,74 @@
+use rusty_anthropic::anthropic_api::{
+    content::ContentBlock,
+    messages::MessageResponse,
+    streaming::{MessageAccumulator, StreamEvent},
+};
+use serde_json::{json, Value};
+
+/// Signature of a thinking block, with characters that must not be escaped
+/// or normalized differently on the way back.
+const SIGNATURE: &str =
+    "EqQBCgIYAhIM1gbcDa9GJwZA2b3hGgxBdjrkzLoky3dl1pkiMOYds+/x0A5TnPH0ni4+Zm1sZXg+\\u002F==";
+
+/// Serialized thinking block as received from the API.
+fn thinking_block() -> String {
+    format!(
+        r#"{{"type":"thinking","thinking":"Let me add 2 and 2.","signature":"{}"}}"#,
+        SIGNATURE.replace('\\', "\\\\")
+    )
+}
+
+#[test]
+fn thinking_block_round_trips_its_signature() {
+    let original = thinking_block();
+
+    let block: ContentBlock = serde_json::from_str(&original).unwrap();
+    let ContentBlock::Thinking { signature, .. } = &block else {
+        panic!("expected a thinking block, got {block:?}");
+    };
+    assert_eq!(signature.as_bytes(), SIGNATURE.as_bytes());
+
+    let reserialized = serde_json::to_string(&block).unwrap();
+    assert_eq!(reserialized, original);
+    assert_eq!(Value::from(block)["signature"], SIGNATURE);
+}
+
+#[test]
+fn response_and_stream_keep_the_signature_for_the_next_turn() {
+    let response = format!(
+        r#"{{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5-20250929","content":[{},{{"type":"text","text":"4"}}],"stop_reason":"end_turn","stop_sequence":null,"usage":{{"input_tokens":10,"output_tokens":20}}}}"#,
+        thinking_block()
+    );
+    let response: MessageResponse = serde_json::from_str(&response).unwrap();
+    let resent = serde_json::to_value(&response).unwrap();
+    assert_eq!(resent["content"][0]["signature"], SIGNATURE);
+
+    let mut accumulator = MessageAccumulator::new();
+    let mut streamed = response.clone();
+    streamed.content.clear();
+    let events = [
+        StreamEvent::MessageStart { message: streamed },
+        StreamEvent::ContentBlockStart {
+            index: 0,
+            content_block: json!({ "type": "thinking", "thinking": "", "signature": "" }),
+        },
+        StreamEvent::ContentBlockDelta {
+            index: 0,
+            delta: json!({ "type": "thinking_delta", "thinking": "Let me add 2 and 2." }),
+        },
+        StreamEvent::ContentBlockDelta {
+            index: 0,
+            delta: json!({ "type": "signature_delta", "signature": SIGNATURE }),
+        },
+        StreamEvent::ContentBlockStop { index: 0 },
+    ];
+    for event in &events {
+        accumulator.push(event);
+    }
+
+    let message = accumulator.finish().unwrap();
+    assert_eq!(
+        serde_json::to_string(&message.content[0]).unwrap(),
+        thinking_block()
+    );
+}
@@ -0,0 +1,58 @@
+use rusty_anthropic::anthropic_api::{
+    messages::{MessageRequest, MessageResponse},
+    tools::Tool,
+};
+use serde_json::json;
+
+#[test]
+fn tools_and_tool_choice_are_sent_and_calls_read_back() {
+    let tool = Tool::new(
+        "get_weather",
+        json!({
+            "type": "object",
+            "properties": { "location": { "type": "string" } },
+            "required": ["location"],
+        }),
+    )
+    .description("Get the current weather in a given location.");
+
+    let request = MessageRequest::new(
+        "claude-sonnet-4-5".into(),
+        vec![json!({ "role": "user", "content": "Weather in Paris?" })],
+    )
+    .tools(vec![tool.into()])
+    .tool_choice(json!({ "type": "tool", "name": "get_weather" }));
+    let request = serde_json::to_value(request).unwrap();
+
+    assert_eq!(request["tools"][0]["name"], "get_weather");
+    assert_eq!(
+        request["tools"][0]["description"],
+        "Get the current weather in a given location."
+    );
+    assert_eq!(
+        request["tools"][0]["input_schema"]["required"][0],
+        "location"
+    );
+    assert_eq!(request["tool_choice"]["name"], "get_weather");
+
+    let response: MessageResponse = serde_json::from_value(json!({
+        "id": "msg_1",
+        "type": "message",
+        "model": "claude-sonnet-4-5",
+        "role": "assistant",
+        "content": [{
+            "type": "tool_use",
+            "id": "toolu_1",
+            "name": "get_weather",
+            "input": { "location": "Paris" },
+        }],
+        "stop_reason": "tool_use",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 10, "output_tokens": 5 },
+    }))
+    .unwrap();
+
+    let calls = response.tool_uses();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].input["location"], "Paris");
+}
@@ -0,0 +1,28 @@
+use rusty_anthropic::anthropic_api::{
+    content::ContentBlock,
+    tokens::{estimate_tokens, truncate_to_tokens, TRUNCATION_MARKER},
+};
+
+#[test]
+fn large_tool_output_is_cut_within_budget() {
+    let output = "fn main() {}\n".repeat(10_000);
+
+    let block = ContentBlock::tool_result("toolu_read", output).truncated(500);
+
+    let ContentBlock::ToolResult { content, .. } = block else {
+        panic!("expected a tool result");
+    };
+    assert!(content.ends_with(TRUNCATION_MARKER));
+    assert!(estimate_tokens(&content) <= 500);
+}
+
+#[test]
+fn text_within_budget_is_unchanged() {
+    let text = "Paris: 18°C, light rain";
+
+    assert_eq!(truncate_to_tokens(text, 100), text);
+    assert_eq!(
+        ContentBlock::tool_result("toolu_weather", text).truncated(100),
+        ContentBlock::tool_result("toolu_weather", text)
+    );
+}
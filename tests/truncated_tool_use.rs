@@ -0,0 +1,43 @@
+use rusty_anthropic::anthropic_api::messages::MessageResponse;
+use serde_json::{json, Value};
+
+/// Build a response ending with a tool call.
+fn response_with_tool_call(stop_reason: &str) -> MessageResponse {
+    let value: Value = json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-5",
+        "content": [
+            { "type": "text", "text": "Let me write the file." },
+            {
+                "type": "tool_use",
+                "id": "toolu_write",
+                "name": "write_file",
+                "input": { "path": "notes.md" },
+            },
+        ],
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": { "input_tokens": 120, "output_tokens": 64 },
+    });
+
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn tool_call_cut_off_by_max_tokens_is_flagged() {
+    let response = response_with_tool_call("max_tokens");
+
+    let truncated = response.truncated_tool_use().unwrap();
+    assert_eq!(truncated.id, "toolu_write");
+    assert_eq!(truncated.name, "write_file");
+}
+
+#[test]
+fn complete_tool_call_is_not_flagged() {
+    let response = response_with_tool_call("tool_use");
+
+    assert!(response.truncated_tool_use().is_none());
+    assert_eq!(response.tool_uses().len(), 1);
+}
@@ -0,0 +1,62 @@
+use rusty_anthropic::anthropic_api::messages::Usage;
+
+#[test]
+fn cache_fields_present_on_one_side_are_kept() {
+    let first = Usage {
+        input_tokens: 100,
+        output_tokens: 20,
+        cache_creation_input_tokens: Some(2048),
+        cache_read_input_tokens: None,
+        service_tier: None,
+    };
+    let second = Usage {
+        input_tokens: 30,
+        output_tokens: 5,
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: Some(2048),
+        service_tier: None,
+    };
+
+    let expected = Usage {
+        input_tokens: 130,
+        output_tokens: 25,
+        cache_creation_input_tokens: Some(2048),
+        cache_read_input_tokens: Some(2048),
+        service_tier: None,
+    };
+    assert_eq!(first + second, expected);
+    assert_eq!(second + first, expected);
+
+    let mut total = first;
+    total += second;
+    assert_eq!(total, expected);
+}
+
+#[test]
+fn cache_fields_missing_on_both_sides_stay_missing() {
+    let usage = Usage {
+        input_tokens: 10,
+        output_tokens: 1,
+        ..Usage::default()
+    };
+
+    let total: Usage = [usage, usage, usage].into_iter().sum();
+    assert_eq!(total.input_tokens, 30);
+    assert_eq!(total.output_tokens, 3);
+    assert_eq!(total.cache_creation_input_tokens, None);
+    assert_eq!(total.cache_read_input_tokens, None);
+}
+
+#[test]
+fn total_counts_cached_tokens() {
+    let usage = Usage {
+        input_tokens: 30,
+        output_tokens: 5,
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: Some(2048),
+        service_tier: None,
+    };
+
+    assert_eq!(usage.total(), 2083);
+    assert_eq!(Usage::default().total(), 0);
+}